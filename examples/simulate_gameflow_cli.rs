@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use werewolf::{request, Error};
+use werewolf::{request, role::Role, Error};
 
 fn input(prompt: &str) -> String {
     loop {
@@ -14,12 +14,52 @@ fn input(prompt: &str) -> String {
     }
 }
 
+/// 役職の表示
+fn describe(role: &Role) -> String {
+    use werewolf::role::Team;
+    let team = |team: &Team| match team {
+        Team::Wolf => "W",
+        Team::Citizen => "C",
+        Team::Fox => "F",
+        Team::Lovers => "L",
+        Team::Jester => "J",
+    };
+    match role {
+        Role::Citizen => "市民".to_string(),
+        Role::Hunter { .. } => "狩人".to_string(),
+        Role::Wolf { .. } => "人狼".to_string(),
+        Role::Madman => "狂人".to_string(),
+        Role::Fox => "妖狐".to_string(),
+        Role::Cupid { .. } => "キューピッド".to_string(),
+        Role::Witch { .. } => "魔女".to_string(),
+        Role::Mason => "共有者".to_string(),
+        Role::Nekomata => "猫又".to_string(),
+        Role::Jester => "道化師".to_string(),
+        Role::Custom { kind, .. } => kind.clone(),
+        Role::Seer { prediction, .. } => {
+            let mut msg = "占い師 - ".to_string();
+            for (k, v) in prediction {
+                msg += &format!("{k}:{}, ", team(v));
+            }
+            msg
+        }
+        Role::Medium { revelation } => {
+            let mut msg = "霊媒師 - ".to_string();
+            for (k, v) in revelation {
+                msg += &format!("{k}:{}, ", team(v));
+            }
+            msg
+        }
+    }
+}
+
 fn main() -> Result<(), Error> {
     use werewolf::master::{config::*, *};
     let mut master = Master::from(Config {
         citizen: CitizenConfig { count: 1 },
         hunter: HunterConfig {
             count: 1,
+            revenge: true,
             ..Default::default()
         },
         wolf: WolfConfig {
@@ -30,15 +70,17 @@ fn main() -> Result<(), Error> {
             count: 1,
             ..Default::default()
         },
+        ..Default::default()
     });
     let tokens = ["はなこ", "たろう", "めじろ", "ぽち"]
         .iter()
         .map(|name| master.signup(name.to_string()))
         .collect::<Result<Vec<_>, _>>()?;
     master.start()?;
+    let first_night = master.config().first_night.count();
     for token in tokens.iter().cycle() {
         use werewolf::role::Role::*;
-        use werewolf::state::State::*;
+        use werewolf::state::{Cause, NightStage, State::*};
 
         let user = master.login(token)?;
         let name = user.name();
@@ -46,8 +88,8 @@ fn main() -> Result<(), Error> {
         println!("-------------------------------------------");
 
         match user.view_state() {
-            End { winner, .. } => {
-                println!("Winner: {:?}", winner);
+            End { winners, .. } => {
+                println!("Winners: {:?}", winners);
                 break;
             }
             Day {
@@ -55,37 +97,23 @@ fn main() -> Result<(), Error> {
                 role,
                 survivors,
                 candidates,
+                deaths,
                 ..
             } => {
                 println!("第{count}日目[昼] {name}");
+                if deaths.is_empty() {
+                    println!("昨夜は誰も死亡しませんでした。");
+                }
+                for death in deaths {
+                    println!("{}が死亡しました。", death.name);
+                }
                 print!("生存者リスト: ");
                 for target in survivors.iter() {
                     print!("{target}, ");
                 }
                 println!();
 
-                println!(
-                    "役職: {}",
-                    match role.get(name).unwrap() {
-                        Citizen => "市民".to_string(),
-                        Hunter { .. } => "狩人".to_string(),
-                        Wolf { .. } => "人狼".to_string(),
-                        Seer { prediction } => {
-                            let mut msg = "占い師 - ".to_string();
-                            for (k, v) in prediction {
-                                use werewolf::role::Team::*;
-                                msg += &format!(
-                                    "{k}:{}, ",
-                                    match v {
-                                        Wolf => "W",
-                                        Citizen => "C",
-                                    }
-                                );
-                            }
-                            msg
-                        }
-                    }
-                );
+                println!("役職: {}", describe(role.get(name).unwrap()));
 
                 if !survivors.contains(name) {
                     println!("{name}は死亡したため行動できません");
@@ -100,7 +128,9 @@ fn main() -> Result<(), Error> {
                 loop {
                     let target = input("投票先 > ");
                     if candidates.contains(&target) {
-                        user.execute(request::Vote { target })?;
+                        user.execute(request::Vote {
+                            target: Some(target),
+                        })?;
                         break;
                     }
                     println!("{target} はリストに含まれていません。");
@@ -109,46 +139,74 @@ fn main() -> Result<(), Error> {
             Night {
                 count,
                 role,
+                waiting,
                 survivors,
+                deaths,
+                tally,
+                no_exile,
+                stage,
                 ..
             } => {
                 println!("第{count}日目[夜]| {name}");
+                for (target, count) in tally.iter() {
+                    println!("{target}: {count}票");
+                }
+                if no_exile > 0 {
+                    println!("誰も追放しない: {no_exile}票");
+                }
+                for death in deaths {
+                    match death.cause {
+                        Cause::Exile => println!("{}が追放されました。", death.name),
+                        Cause::Dragged => println!("{}が道連れにされました。", death.name),
+                        _ => println!("{}が死亡しました。", death.name),
+                    }
+                }
                 print!("生存者リスト: ");
                 for target in survivors.iter() {
                     print!("{target}, ");
                 }
                 println!();
 
-                println!(
-                    "役職: {}",
-                    match role.get(name).unwrap() {
-                        Citizen => "市民".to_string(),
-                        Hunter { .. } => "狩人".to_string(),
-                        Wolf { .. } => "人狼".to_string(),
-                        Seer { prediction } => {
-                            let mut msg = "占い師 - ".to_string();
-                            for (k, v) in prediction {
-                                use werewolf::role::Team::*;
-                                msg += &format!(
-                                    "{k}:{}, ",
-                                    match v {
-                                        Wolf => "W",
-                                        Citizen => "C",
-                                    }
-                                );
-                            }
-                            msg
-                        }
-                    }
-                );
+                println!("役職: {}", describe(role.get(name).unwrap()));
 
                 if !survivors.contains(name) {
                     println!("{name}は死亡したため行動できません");
                     continue;
                 }
+                if !waiting.contains(name) {
+                    continue;
+                }
 
                 match role.get(name).unwrap() {
-                    Citizen => {
+                    Witch { .. } if matches!(stage, NightStage::Witch { .. }) => loop {
+                        if let NightStage::Witch {
+                            attack: Some(attack),
+                        } = &stage
+                        {
+                            println!("今夜は{attack}が襲撃されます。");
+                        }
+                        let req = request::Potion {
+                            heal: input("治療しますか? (y/n) > ") == "y",
+                            poison: Some(input("毒を盛る先 (空欄で使わない) > "))
+                                .filter(|target| !target.is_empty()),
+                        };
+                        match user.validate(&req) {
+                            Ok(()) => {
+                                user.execute(req)?;
+                                break;
+                            }
+                            Err(err) => println!("{err}"),
+                        }
+                    },
+                    Citizen
+                    | Medium { .. }
+                    | Madman
+                    | Fox
+                    | Witch { .. }
+                    | Mason
+                    | Nekomata
+                    | Jester
+                    | Custom { .. } => {
                         println!("あなたは何もすることがありません。");
                         user.execute(request::Skip())?;
                     }
@@ -176,6 +234,19 @@ fn main() -> Result<(), Error> {
                         }
                         println!("{target} は生存者に含まれていません。");
                     },
+                    Cupid { .. } if count == first_night => loop {
+                        let req = request::Bind {
+                            lovers: (input("恋人1 > "), input("恋人2 > ")),
+                        };
+                        match user.validate(&req) {
+                            Ok(()) => {
+                                user.execute(req)?;
+                                break;
+                            }
+                            Err(err) => println!("{err}"),
+                        }
+                    },
+                    Cupid { .. } => user.execute(request::Skip())?,
                     Seer { .. } => loop {
                         let target = input("占い先 > ");
                         if survivors.contains(&target) {
@@ -190,6 +261,24 @@ fn main() -> Result<(), Error> {
                     },
                 };
             }
+            Revenge { waiting, .. } => {
+                if !waiting.contains(name) {
+                    continue;
+                }
+                println!("{name}は死亡しました。道連れにする人を選んでください。");
+                loop {
+                    let req = request::Shoot {
+                        target: input("道連れ先 > "),
+                    };
+                    match user.validate(&req) {
+                        Ok(()) => {
+                            user.execute(req)?;
+                            break;
+                        }
+                        Err(err) => println!("{err}"),
+                    }
+                }
+            }
             _ => unreachable!(),
         }
     }
@@ -0,0 +1,38 @@
+use crate::{
+    request::Any,
+    state::{Name, Phase, State},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// ゲーム中に起きた出来事
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Event {
+    /// ユーザーが登録された
+    Signup {
+        /// 登録された名前
+        name: Name,
+    },
+    /// リクエストが受理された
+    Request {
+        /// リクエスト元
+        name: Name,
+        /// リクエストが適用された場面
+        phase: Phase,
+        /// リクエストの内容
+        request: Any,
+    },
+    /// 締め切りを過ぎたため、行動していない人に既定の行動をとらせた
+    Timeout {
+        /// 締め切りを過ぎた場面
+        phase: Phase,
+    },
+    /// 場面が移行した
+    Transition {
+        /// 移行前の場面
+        from: Phase,
+        /// 移行後の状態
+        to: State,
+    },
+}
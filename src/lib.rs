@@ -1,26 +1,30 @@
+pub mod event;
+pub mod lobby;
 pub mod master;
+pub mod message;
 pub mod request;
 pub mod role;
 pub mod state;
 
+use crate::event::Event;
+use crate::lobby::Error as LobbyError;
 use crate::master::Config;
 use crate::master::Error as AuthError;
 use crate::request::Error as RequestError;
-use crate::request::Request;
-use crate::state::{Name, State};
+use crate::request::{Any, Request};
+use crate::state::{Cause, Death, Name, NightStage, State};
 
 pub use master::Master;
+use rand::{seq::SliceRandom, Rng};
 use serde::Serialize;
 
-use std::{
-    cell::Cell,
-    collections::{HashMap, HashSet},
-};
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
 /// エラー一覧
+/// シリアライズすると、エラーコードを`code`に、詳細を`detail`に持つ。
 #[derive(Error, Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(untagged)]
 pub enum Error {
     /// 認証時のエラー
     #[error("AuthError: {0}")]
@@ -28,223 +32,890 @@ pub enum Error {
     /// リクエスト処理時のエラー
     #[error("RequestFailed: {0}")]
     RequestFailed(#[from] RequestError),
+    /// ロビーでのエラー
+    #[error("LobbyError: {0}")]
+    Lobby(#[from] LobbyError),
+}
+
+impl Error {
+    /// エラーコードを返す。
+    /// エラーコードは各エラーのバリアント名をcamelCaseにしたもので、全てのエラーの間で一意となる。
+    /// # Example
+    /// ```
+    /// use werewolf::{master::Master, request};
+    /// let mut master = Master::new();
+    /// let token = master.signup("たろう".to_string()).unwrap();
+    /// let err = master.login(&token).unwrap().execute(request::Skip()).unwrap_err();
+    /// assert_eq!(err.code(), "invalidState");
+    /// assert_eq!(
+    ///     serde_json::to_value(&err).unwrap()["code"],
+    ///     serde_json::json!("invalidState"),
+    /// );
+    /// ```
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Auth(err) => err.code(),
+            Self::RequestFailed(err) => err.code(),
+            Self::Lobby(err) => err.code(),
+        }
+    }
 }
 
 /// リクエストを処理する権限
 /// Permissionが作成されると、Permissionがドロップされるまで
-/// Masterはイミュータブルになることに留意。
+/// Masterは他から借用できなくなることに留意。
 pub struct Permission<'master> {
-    name: &'master Name,
-    state: &'master mut Cell<State>,
-    config: &'master mut Config,
+    name: Name,
+    master: &'master mut Master,
 }
 
 impl<'master> Permission<'master> {
     /// リクエストを実行する
     pub fn execute(self, req: impl Request<'master>) -> Result<(), Error> {
-        let Self {
+        let Self { name, master } = self;
+        let req: Any = req.into();
+        let state = &mut master.state;
+        req.clone().modify(&name, state, &master.config)?;
+
+        let phase = state.phase();
+        master.log.push(Event::Request {
             name,
-            state,
-            config,
-        } = self;
-        req.modify(name, state.get_mut(), config)?;
-
-        /// 勝敗の決定
-        macro_rules! judge {
-            ($survivors: expr, $role: expr) => {
-                let mut wolves = 0;
-                for name in $survivors.iter() {
-                    if let Some(role::Role::Wolf { .. }) = $role.get(name) {
-                        wolves += 1;
-                    }
+            phase,
+            request: req,
+        });
+        master.proceed(phase);
+        Ok(())
+    }
+
+    /// パーミッション元ユーザの名前を返す。
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    /// 閲覧用の権限を得る。
+    pub fn viewer(&self) -> Viewer<'_> {
+        Viewer {
+            name: &self.name,
+            master: self.master,
+        }
+    }
+
+    /// Stateをクローンし、そのユーザーが閲覧できる範囲にフィルターして返す
+    pub fn view_state(&self) -> State {
+        self.viewer().view_state()
+    }
+
+    /// 出来事の記録をクローンし、そのユーザーが閲覧できる範囲にフィルターして返す。
+    /// ゲーム終了後は全ての記録を返す。
+    pub fn history(&self) -> Vec<Event> {
+        self.viewer().history()
+    }
+
+    /// 現在実行可能なリクエストと、その対象の一覧を返す。
+    pub fn available_actions(&self) -> Vec<request::Action> {
+        self.viewer().available_actions()
+    }
+
+    /// リクエストを実行せずに、実行した場合に起きるエラーを返す。
+    pub fn validate(&self, req: &impl Request<'master>) -> Result<(), request::Error> {
+        self.viewer().validate(req)
+    }
+}
+
+/// 状態を閲覧する権限
+/// Permissionと異なりMasterを共有参照で借用するため、複数のユーザーが同時に閲覧できる。
+pub struct Viewer<'master> {
+    name: &'master Name,
+    master: &'master Master,
+}
+
+impl Viewer<'_> {
+    /// 閲覧元ユーザの名前を返す。
+    pub fn name(&self) -> &Name {
+        self.name
+    }
+
+    /// 現在実行可能なリクエストと、その対象の一覧を返す。
+    /// # Example
+    /// ```
+    /// use werewolf::{master::{config::*, Master}, request::Kind};
+    /// let mut master = Master::from(Config {
+    ///     citizen: CitizenConfig { count: 1 },
+    ///     wolf: WolfConfig { count: 1, ..Default::default() },
+    ///     ..Default::default()
+    /// });
+    /// let token = master.signup("たろう".to_string()).unwrap();
+    /// master.signup("はなこ".to_string()).unwrap();
+    /// master.start().unwrap();
+    /// let actions = master.viewer(&token).unwrap().available_actions();
+    /// assert_eq!(actions.len(), 1);
+    /// assert!(matches!(actions[0].kind, Kind::Kill | Kind::Skip));
+    /// ```
+    pub fn available_actions(&self) -> Vec<request::Action> {
+        request::available_actions(self.name, &self.master.state, &self.master.config)
+    }
+
+    /// リクエストを実行せずに、実行した場合に起きるエラーを返す。
+    /// # Example
+    /// ```
+    /// use werewolf::{master::{config::*, Master}, request};
+    /// let mut master = Master::from(Config {
+    ///     citizen: CitizenConfig { count: 1 },
+    ///     wolf: WolfConfig { count: 1, ..Default::default() },
+    ///     ..Default::default()
+    /// });
+    /// let token = master.signup("たろう".to_string()).unwrap();
+    /// master.signup("はなこ".to_string()).unwrap();
+    /// master.start().unwrap();
+    /// let before = master.viewer(&token).unwrap().view_state();
+    /// let viewer = master.viewer(&token).unwrap();
+    /// let vote = request::Vote { target: Some("はなこ".to_string()) };
+    /// assert!(matches!(
+    ///     viewer.validate(&vote),
+    ///     Err(request::Error::InvalidState { .. })
+    /// ));
+    /// assert_eq!(viewer.view_state(), before);
+    /// ```
+    pub fn validate<'req>(&self, req: &impl Request<'req>) -> Result<(), request::Error> {
+        req.validate(self.name, &self.master.state, &self.master.config)
+    }
+
+    /// 自分のロールと、自分の役職が見ることのできるロールのみにフィルターする。
+    /// 人狼は仲間の人狼を、共有者は他の共有者を見ることができる。
+    fn filter_role(&self, mut role: HashMap<Name, role::Role>) -> HashMap<Name, role::Role> {
+        let Some(behavior) = role.get(self.name).map(role::Role::behavior) else {
+            return HashMap::new();
+        };
+        role.drain()
+            .filter(|(k, v)| k == self.name || behavior.sees(v))
+            .collect()
+    }
+
+    /// Stateをそのユーザーが閲覧できる範囲にフィルターする
+    fn filter_state(&self, mut state: State) -> State {
+        if let State::Day { role, lovers, .. } | State::Night { role, lovers, .. } = &mut state {
+            // 恋人たちは本人と、結びつけたキューピッドにのみ見える
+            let bound = match role.get(self.name) {
+                Some(role::Role::Cupid { bound, .. }) => bound.clone(),
+                _ => None,
+            };
+            lovers.retain(|pair| {
+                pair.0 == *self.name || pair.1 == *self.name || Some(pair) == bound.as_ref()
+            });
+        }
+        if let State::Day { role, deaths, .. } = &mut state {
+            // 夜の間の死因は、それを知ることのできる人以外には区別せずに伏せる
+            let own = role.get(self.name);
+            for death in deaths.iter_mut() {
+                if !knows_cause(own, death) {
+                    death.cause = Cause::Night;
                 }
-                use role::Team::{Citizen, Wolf};
-                if wolves * 2 >= $survivors.len() {
-                    *state = State::End {
-                        winner: Wolf,
-                        role: $role,
-                    }
-                    .into();
-                    return Ok(());
-                } else if wolves == 0 {
-                    *state = State::End {
-                        winner: Citizen,
-                        role: $role,
-                    }
-                    .into();
-                    return Ok(());
+            }
+        }
+        if let State::Night {
+            role,
+            waiting,
+            ready,
+            stage,
+            ..
+        } = &mut state
+        {
+            // 魔女の行動する段階は魔女にのみ見え、他の人には全員の行動が揃った夜に見える
+            let witch = matches!(role.get(self.name), Some(role::Role::Witch { .. }));
+            if !witch && matches!(stage, NightStage::Witch { .. }) {
+                *stage = NightStage::Main;
+                // 行動を確定した人がいれば、魔女も確定していたように見せる
+                if !ready.is_empty() {
+                    ready.extend(waiting.iter().cloned());
+                }
+                waiting.clear();
+            }
+        }
+        if let State::Day { role, .. } | State::Night { role, .. } = &mut state {
+            *role = self.filter_role(std::mem::take(role));
+        }
+        if let State::Revenge { next, .. } = &mut state {
+            **next = self.filter_state(std::mem::take(next));
+        }
+        state
+    }
+
+    /// Stateをクローンし、そのユーザーが閲覧できる範囲にフィルターして返す
+    /// # Example
+    /// ```
+    /// use werewolf::{master::{config::*, Master}, role::Role, state::State};
+    /// let mut master = Master::from(Config {
+    ///     wolf: WolfConfig { count: 1, ..Default::default() },
+    ///     mason: MasonConfig { count: 2 },
+    ///     ..Default::default()
+    /// });
+    /// let tokens: Vec<_> = ["たろう", "はなこ", "めじろ"]
+    ///     .iter()
+    ///     .map(|name| master.signup(name.to_string()).unwrap())
+    ///     .collect();
+    /// master.start().unwrap();
+    /// for token in tokens.iter() {
+    ///     let viewer = master.viewer(token).unwrap();
+    ///     let State::Night { role, .. } = viewer.view_state() else { unreachable!() };
+    ///     // 共有者は互いに見え、人狼は自分だけが見える
+    ///     let visible = if role[viewer.name()] == Role::Mason { 2 } else { 1 };
+    ///     assert_eq!(role.len(), visible);
+    /// }
+    /// ```
+    ///
+    /// 夜の間の死因は、それを知ることのできる人にのみ見える。
+    pub fn view_state(&self) -> State {
+        let state = self.master.state.clone();
+        self.filter_state(state)
+    }
+
+    /// 出来事の記録をクローンし、そのユーザーが閲覧できる範囲にフィルターして返す。
+    /// ゲーム終了後は全ての記録を返す。
+    pub fn history(&self) -> Vec<Event> {
+        let current = match &self.master.state {
+            State::Revenge { next, .. } => &**next,
+            state => state,
+        };
+        let role = match current {
+            State::Waiting { .. } | State::Revenge { .. } => None,
+            State::Day { role, .. } | State::Night { role, .. } => Some(role),
+            State::End { .. } => return self.master.log.clone(),
+        };
+        let is_wolf = |name: &Name| {
+            role.and_then(|role| role.get(name))
+                .is_some_and(role::Role::is_wolf)
+        };
+        let is_witch = role
+            .and_then(|role| role.get(self.name))
+            .is_some_and(|role| matches!(role, role::Role::Witch { .. }));
+        // 魔女の行動する段階の出来事は、魔女以外には伏せる
+        let mut hidden = false;
+        self.master
+            .log
+            .iter()
+            .filter_map(|event| match event {
+                Event::Request { name, .. } if hidden && name != self.name => None,
+                Event::Timeout { .. } if hidden => None,
+                Event::Request { name, request, .. } => {
+                    let visible = name == self.name
+                        || match request {
+                            // 公開されているリクエスト
+                            Any::UpdateConfig(_)
+                            | Any::Vote(_)
+                            | Any::Abstain(_)
+                            | Any::Ready(_)
+                            | Any::Shoot(_) => true,
+                            // 仲間の人狼の襲撃先
+                            Any::Kill(_) => is_wolf(self.name) && is_wolf(name),
+                            _ => false,
+                        };
+                    visible.then(|| event.clone())
+                }
+                Event::Signup { .. } | Event::Timeout { .. } => Some(event.clone()),
+                Event::Transition { from, to } => {
+                    hidden = !is_witch
+                        && matches!(
+                            to,
+                            State::Night {
+                                stage: NightStage::Witch { .. },
+                                ..
+                            }
+                        );
+                    (!hidden).then(|| Event::Transition {
+                        from: *from,
+                        to: self.filter_state(to.clone()),
+                    })
                 }
+            })
+            .collect()
+    }
+}
+
+/// 夜の間の死亡について、その死因を知ることができるかどうか。
+fn knows_cause(role: Option<&role::Role>, death: &Death) -> bool {
+    match death.cause {
+        // 狩人や猫又の道連れ先は、通常の死亡と区別して公開されている
+        Cause::Revenge | Cause::Dragged => true,
+        // 襲撃した人狼
+        Cause::Attack => role.is_some_and(role::Role::is_wolf),
+        // 呪殺された人を占った占い師
+        Cause::Curse => matches!(
+            role,
+            Some(role::Role::Seer { prediction, .. }) if prediction.contains_key(&death.name)
+        ),
+        // 毒の薬を持つ魔女
+        Cause::Poison => matches!(role, Some(role::Role::Witch { .. })),
+        _ => false,
+    }
+}
+
+/// 締め切りを過ぎた場面で、行動していない人に既定の行動をとらせる。
+/// 既定の行動がとれない場合は、行動せずに待機を終える。
+pub(crate) fn apply_defaults(state: &mut State, config: &Config, rng: &mut impl Rng) {
+    use master::config::{DayDefault, NightDefault};
+    let mut names: Vec<_> = match state {
+        State::Day { waiting, .. }
+        | State::Night { waiting, .. }
+        | State::Revenge { waiting, .. } => waiting.iter().cloned().collect(),
+        State::Waiting { .. } | State::End { .. } => return,
+    };
+    names.sort();
+    for name in names {
+        let req: Option<Any> = match state {
+            State::Day { candidates, .. } => match config.time_limit.day_default {
+                DayDefault::Abstain => None,
+                DayDefault::RandomVote => {
+                    let mut targets: Vec<_> = candidates.iter().filter(|c| *c != &name).collect();
+                    targets.sort();
+                    targets.choose(rng).map(|&target| {
+                        request::Vote {
+                            target: Some(target.to_owned()),
+                        }
+                        .into()
+                    })
+                }
+            },
+            State::Night {
+                role, survivors, ..
+            } => match config.time_limit.night_default {
+                NightDefault::Skip => None,
+                NightDefault::RandomAction => {
+                    let mut targets: Vec<_> = survivors.iter().filter(|s| *s != &name).collect();
+                    targets.sort();
+                    let target = targets.choose(rng).map(|&target| target.to_owned());
+                    let kind = role
+                        .get(&name)
+                        .and_then(|role| role.behavior().night_action());
+                    target
+                        .zip(kind)
+                        .and_then(|(target, kind)| kind.with_target(target))
+                }
+            },
+            // 締め切りを過ぎると誰も道連れにしない
+            State::Waiting { .. } | State::Revenge { .. } | State::End { .. } => None,
+        };
+        if req.is_some_and(|req| req.modify(&name, state, config).is_ok()) {
+            continue;
+        }
+        if let State::Day { waiting, .. }
+        | State::Night { waiting, .. }
+        | State::Revenge { waiting, .. } = state
+        {
+            waiting.remove(&name);
+        }
+        if let State::Night { role, .. } = state {
+            if let Some(role) = role.get_mut(&name) {
+                role.clear_target();
+            }
+        }
+    }
+    // 締め切りでは全員の行動を確定する
+    if let State::Day {
+        ready, survivors, ..
+    }
+    | State::Night {
+        ready, survivors, ..
+    } = state
+    {
+        ready.extend(survivors.iter().cloned());
+    }
+}
+
+/// 場面を解決できるかどうか。
+/// 行動を変更できる場合は、生存者全員が行動を確定している必要がある。
+fn settled(
+    waiting: &HashSet<Name>,
+    ready: &HashSet<Name>,
+    survivors: &HashSet<Name>,
+    config: &Config,
+) -> bool {
+    waiting.is_empty() && (!config.action.revisable || survivors.is_subset(ready))
+}
+
+/// 勝敗を判定し、決着していれば終了の場面に置きかえる。
+fn judge(next: State) -> State {
+    if let State::Day {
+        role,
+        survivors,
+        lovers,
+        ..
+    }
+    | State::Night {
+        role,
+        survivors,
+        lovers,
+        ..
+    } = &next
+    {
+        // 最後の二人が陣営の異なる恋人であれば、恋人たちが共に勝利する
+        let lovers_win = survivors.len() == 2
+            && lovers.iter().any(|(first, second)| {
+                survivors.contains(first)
+                    && survivors.contains(second)
+                    && role[first].team() != role[second].team()
+            });
+        if lovers_win {
+            return State::End {
+                winners: vec![role::Winner::Team {
+                    team: role::Team::Lovers,
+                }],
+                role: role.clone(),
             };
         }
+        if let Some(team) = role::winner(survivors, role) {
+            return State::End {
+                winners: vec![role::Winner::Team { team }],
+                role: role.clone(),
+            };
+        }
+    }
+    next
+}
 
-        match state.get_mut().clone() {
-            State::Waiting {
-                config: next_config,
-            } => {
-                // 設定が変更されたら書きかえる
-                *config = next_config;
+/// 死亡した人の恋人を後追いさせた上で、道連れのできる狩人がいれば道連れの場面を挟み、
+/// いなければ勝敗を判定して次の場面へ移行する。
+fn advance(count: usize, mut next: State, mut dead: Vec<Death>, config: &Config) -> State {
+    // 恋人の後追いは連鎖する
+    let lovers = match &next {
+        State::Day { lovers, .. } | State::Night { lovers, .. } => lovers.clone(),
+        _ => Vec::new(),
+    };
+    let mut i = 0;
+    while i < dead.len() {
+        for (first, second) in lovers.iter() {
+            let partner = match &dead[i].name {
+                name if name == first => second,
+                name if name == second => first,
+                _ => continue,
+            };
+            let death = Death {
+                name: partner.to_owned(),
+                cause: Cause::Heartbreak,
+            };
+            if kill(&mut next, &death) {
+                dead.push(death);
             }
-            State::Day {
-                count,
-                role,
-                waiting,
-                mut survivors,
-                votes,
-                ..
-            } => {
-                if waiting.is_empty() {
-                    let candidates: HashSet<_> = {
-                        // 最大票数獲得者の絞りこみ
-                        let mut freqs = HashMap::new();
-                        for target in votes.values() {
-                            *freqs.entry(target).or_insert(0) += 1;
-                        }
+        }
+        i += 1;
+    }
 
-                        let max_count = freqs.values().cloned().max().unwrap_or(0);
-                        freqs
-                            .into_iter()
-                            .filter_map(|(candidate, count)| {
-                                if count == max_count {
-                                    Some(candidate.clone())
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect()
-                    };
+    let waiting: HashSet<_> = match &next {
+        State::Day { role, .. } | State::Night { role, .. } if config.hunter.revenge => dead
+            .iter()
+            .filter(|death| matches!(role.get(&death.name), Some(role::Role::Hunter { .. })))
+            .map(|death| death.name.clone())
+            .collect(),
+        _ => HashSet::new(),
+    };
+    if waiting.is_empty() {
+        judge(next)
+    } else {
+        State::Revenge {
+            count,
+            waiting,
+            shots: HashMap::new(),
+            next: Box::new(next),
+        }
+    }
+}
 
-                    if candidates.len() == 1 {
-                        // 候補者が一人に定まった場合
+/// 死亡した人の役職が道連れにする候補から、それぞれランダムに一人を選んで生存者から取り除く。
+/// 道連れにされた人の死亡を返す。
+fn drag(
+    dead: &[Death],
+    role: &HashMap<Name, role::Role>,
+    survivors: &mut HashSet<Name>,
+    rng: &mut impl Rng,
+) -> Vec<Death> {
+    let mut dragged = Vec::new();
+    for death in dead.iter() {
+        let mut targets = role[&death.name]
+            .behavior()
+            .drags(&death.cause, role, survivors);
+        targets.sort();
+        if let Some(target) = targets.choose(rng) {
+            survivors.remove(target);
+            dragged.push(Death {
+                name: target.to_owned(),
+                cause: Cause::Dragged,
+            });
+        }
+    }
+    dragged
+}
 
-                        // 追放
-                        survivors.remove(&candidates.iter().next().unwrap().to_owned());
+/// 次の場面の生存者から取り除き、死亡を記録する。
+/// 既に死亡していた場合はfalseを返す。
+fn kill(next: &mut State, death: &Death) -> bool {
+    if let State::Day { candidates, .. } = next {
+        candidates.remove(&death.name);
+    }
+    let (State::Day {
+        waiting,
+        survivors,
+        deaths,
+        ..
+    }
+    | State::Night {
+        waiting,
+        survivors,
+        deaths,
+        ..
+    }) = next
+    else {
+        return false;
+    };
+    if !survivors.remove(&death.name) {
+        return false;
+    }
+    waiting.remove(&death.name);
+    deaths.push(death.clone());
+    true
+}
 
-                        // 勝敗判定
-                        judge!(survivors, role);
+/// 全員の行動が揃った場面を解決し、次の場面へ移行する。
+/// 場面が移行した場合はtrueを返す。
+pub(crate) fn resolve(state: &mut State, config: &mut Config, rng: &mut impl Rng) -> bool {
+    match state.clone() {
+        State::Waiting {
+            config: next_config,
+        } => {
+            // 設定が変更されたら書きかえる
+            *config = next_config;
+            false
+        }
+        State::Day {
+            count,
+            mut role,
+            waiting,
+            ready,
+            mut survivors,
+            lovers,
+            votes,
+            deaths,
+            runoff,
+            ..
+        } => {
+            if settled(&waiting, &ready, &survivors, config) {
+                // 得票数
+                let (mut tally, mut no_exile) = (HashMap::new(), 0);
+                for target in votes.values() {
+                    match target {
+                        Some(target) => *tally.entry(target.to_owned()).or_insert(0) += 1,
+                        None => no_exile += 1,
+                    }
+                }
 
-                        // 次の夜がやってきました。
-                        *state = State::Night {
-                            count: count + 1,
-                            role,
-                            waiting: survivors.clone(),
-                            survivors,
+                // 最大票数獲得者の絞りこみ
+                let max_count = tally.values().cloned().max().unwrap_or(0);
+                let mut candidates: Vec<_> = tally
+                    .iter()
+                    .filter_map(|(candidate, count)| {
+                        if *count == max_count {
+                            Some(candidate.clone())
+                        } else {
+                            None
                         }
-                        .into();
-                    } else {
+                    })
+                    .collect();
+                candidates.sort();
+
+                // 追放される人と、適用された同票時の処理
+                let (exiled, tie_break) = if no_exile >= max_count {
+                    // 誰も追放しない票が最多(同数を含む)の場合や、全員が棄権した場合
+                    (Vec::new(), None)
+                } else if candidates.len() == 1 {
+                    // 候補者が一人に定まった場合
+                    (candidates, None)
+                } else {
+                    use master::config::TieBreak::*;
+                    let tie_break = config.vote.tie_break.clone();
+                    let exiled = match tie_break {
+                        Runoff => None,
+                        RunoffLimit(limit) if runoff < limit => None,
+                        RunoffOnceThenRandom | RunoffOnceThenNobody if runoff < 1 => None,
+                        RunoffOnceThenRandom => candidates.choose(rng).cloned().map(|c| vec![c]),
+                        RunoffOnceThenNobody | RunoffLimit(_) => Some(Vec::new()),
+                        AllExiled => Some(candidates.clone()),
+                    };
+                    let Some(exiled) = exiled else {
                         // 決選投票
                         *state = State::Day {
                             count,
                             role,
                             waiting: survivors.clone(),
+                            ready: HashSet::new(),
                             survivors,
+                            lovers,
                             votes: HashMap::new(),
-                            candidates,
+                            candidates: candidates.into_iter().collect(),
+                            deaths,
+                            runoff: runoff + 1,
+                        };
+                        return true;
+                    };
+                    (exiled, Some(tie_break))
+                };
+
+                // 追放
+                for exiled in exiled.iter() {
+                    survivors.remove(exiled);
+                }
+
+                // 霊媒
+                for exiled in exiled.iter() {
+                    let team = role[exiled].divined_as();
+                    for name in survivors.iter() {
+                        if let Some(role::Role::Medium { revelation }) = role.get_mut(name) {
+                            revelation.insert(exiled.clone(), team.clone());
                         }
-                        .into();
                     }
                 }
+
+                let mut deaths: Vec<_> = exiled
+                    .into_iter()
+                    .map(|name| Death {
+                        name,
+                        cause: Cause::Exile,
+                    })
+                    .collect();
+                // 追放されて勝利する人がいれば、その場で終了する
+                let winners: Vec<_> = deaths
+                    .iter()
+                    .filter(|death| role[&death.name].behavior().wins_by(&death.cause))
+                    .map(|death| role::Winner::Solo {
+                        name: death.name.clone(),
+                    })
+                    .collect();
+                if !winners.is_empty() {
+                    *state = State::End { role, winners };
+                    return true;
+                }
+
+                // 追放された猫又の道連れ
+                let dragged = drag(&deaths, &role, &mut survivors, rng);
+                deaths.extend(dragged);
+
+                // 次の夜がやってきました。
+                let next = State::Night {
+                    count: count + 1,
+                    role,
+                    waiting: survivors.clone(),
+                    ready: HashSet::new(),
+                    survivors,
+                    lovers,
+                    deaths: deaths.clone(),
+                    tally,
+                    no_exile,
+                    tie_break,
+                    stage: NightStage::Main,
+                };
+                // 後追いと道連れと勝敗判定
+                *state = advance(count, next, deaths, config);
+                true
+            } else {
+                false
             }
-            State::Night {
-                count,
-                role,
-                waiting,
-                mut survivors,
-            } => {
-                if waiting.is_empty() {
-                    {
-                        // 殺害
-                        let (mut guardings, mut targets) = (Vec::new(), Vec::new());
-                        for name in survivors.iter() {
-                            use role::Role::{Hunter, Wolf};
-                            match role.get(name) {
-                                Some(Hunter {
-                                    guarding: Some(guard),
-                                }) => {
-                                    guardings.push(guard);
-                                }
-                                Some(Wolf {
-                                    killing: Some(kill),
-                                }) => {
-                                    targets.push(kill);
+        }
+        State::Night {
+            count,
+            mut role,
+            waiting,
+            ready,
+            mut survivors,
+            mut lovers,
+            stage,
+            ..
+        } => {
+            if settled(&waiting, &ready, &survivors, config) {
+                let mut deaths = {
+                    // 優先度の順に、各自の行動を解決する
+                    let mut night = role::behavior::Night::new(&role, &survivors);
+                    if let NightStage::Witch { attack } = &stage {
+                        night.attack = attack.clone();
+                    }
+                    let mut actors: Vec<_> = survivors
+                        .iter()
+                        .filter_map(|name| role.get(name).map(|role| (name, role)))
+                        .collect();
+                    actors.sort_by_key(|(name, role)| (role.behavior().priority(), *name));
+                    for (name, role) in actors {
+                        role.behavior()
+                            .resolve_night(name, role.target(), &mut night);
+                    }
+                    let role::behavior::Night {
+                        guarded,
+                        proposals,
+                        divined,
+                        lovers: bound,
+                        mut deaths,
+                        ..
+                    } = night;
+                    // 結びつけられた恋人たち
+                    for pair in bound {
+                        if !lovers.contains(&pair) {
+                            lovers.push(pair);
+                        }
+                    }
+                    let is_dead = |deaths: &[Death], name: &Name| {
+                        deaths.iter().any(|death| &death.name == name)
+                    };
+                    let attack = match stage {
+                        NightStage::Witch { attack } => attack,
+                        NightStage::Main => {
+                            // 人狼たちの提案から一人の襲撃先を決定する
+                            let attack = config.wolf.decision.decide(&proposals, rng).cloned();
+                            // 薬の残っている魔女がいれば、襲撃先を知らせて行動させる
+                            let witches: HashSet<_> = survivors
+                                .iter()
+                                .filter(|name| {
+                                    matches!(
+                                        role.get(*name),
+                                        Some(role::Role::Witch {
+                                            heal_used,
+                                            poison_used,
+                                            ..
+                                        }) if !(*heal_used && *poison_used)
+                                    )
+                                })
+                                .cloned()
+                                .collect();
+                            if !witches.is_empty() {
+                                if let State::Night {
+                                    waiting,
+                                    ready,
+                                    lovers: bound,
+                                    stage,
+                                    ..
+                                } = state
+                                {
+                                    ready.retain(|name| !witches.contains(name));
+                                    *waiting = witches;
+                                    *bound = lovers;
+                                    *stage = NightStage::Witch { attack };
                                 }
-                                _ => (),
+                                return true;
                             }
+                            attack
                         }
-                        for kill in targets {
-                            if !guardings.contains(&kill) {
-                                // 守られていない人
-                                survivors.remove(kill);
-                            }
+                    };
+                    if let Some(kill) = attack {
+                        // 守られていない人 (妖狐は襲撃では死なない)
+                        if !guarded.contains(&kill)
+                            && role[&kill].behavior().killable()
+                            && !is_dead(&deaths, &kill)
+                        {
+                            deaths.push(Death {
+                                name: kill,
+                                cause: Cause::Attack,
+                            });
                         }
                     }
-
-                    // 勝敗判定
-                    judge!(survivors, role);
-
-                    // 次の夜がやってきました。
-                    *state = State::Day {
-                        count: count + 1,
-                        role,
-                        waiting: survivors.clone(),
-                        candidates: survivors.clone(),
-                        votes: HashMap::new(),
-                        survivors,
+                    for divine in divined.iter() {
+                        // 占われた妖狐
+                        if role[divine].behavior().cursed() && !is_dead(&deaths, divine) {
+                            deaths.push(Death {
+                                name: divine.to_owned(),
+                                cause: Cause::Curse,
+                            });
+                        }
+                    }
+                    deaths.sort_by(|a, b| a.name.cmp(&b.name));
+                    deaths
+                };
+                // 行動を変更できる場合は、確定した占い先の結果をここで知らせる
+                if config.action.revisable {
+                    let results: Vec<_> = survivors
+                        .iter()
+                        .filter_map(|name| match role.get(name) {
+                            Some(role::Role::Seer {
+                                divining: Some(divine),
+                                ..
+                            }) => Some((name.clone(), divine.clone(), role[divine].divined_as())),
+                            _ => None,
+                        })
+                        .collect();
+                    for (name, divine, team) in results {
+                        if let Some(role::Role::Seer { prediction, .. }) = role.get_mut(&name) {
+                            prediction.insert(divine, team);
+                        }
                     }
-                    .into();
                 }
-            }
-            State::End { .. } => {}
-        }
-        Ok(())
-    }
-
-    /// パーミッション元ユーザの名前を返す。
-    pub fn name(&self) -> &Name {
-        self.name
-    }
+                for role in role.values_mut() {
+                    // 今夜の防護先を、次の夜の連続防護の判定に用いる
+                    if let role::Role::Hunter { guarding, guarded } = role {
+                        *guarded = guarding.take();
+                    }
+                    // 結びつけた二人は、以降の夜にスキップしても取り消されない
+                    if let role::Role::Cupid { binding, bound } = role {
+                        if let Some(pair) = binding.take() {
+                            *bound = Some(pair);
+                        }
+                    }
+                    // 使った薬はなくなる
+                    if let role::Role::Witch {
+                        heal_used,
+                        poison_used,
+                        healing,
+                        poisoning,
+                    } = role
+                    {
+                        *heal_used |= std::mem::take(healing);
+                        *poison_used |= poisoning.take().is_some();
+                    }
+                }
+                for death in deaths.iter() {
+                    survivors.remove(&death.name);
+                }
+                // 襲撃された猫又の道連れ
+                let dragged = drag(&deaths, &role, &mut survivors, rng);
+                deaths.extend(dragged);
 
-    /// Stateをクローンし、そのユーザーが閲覧できる範囲にフィルターして返す
-    pub fn view_state(&self) -> State {
-        let state = unsafe { (*self.state.as_ptr()).clone() };
-        use State::*;
-        match state {
-            Waiting { .. } | End { .. } => state,
-            Day {
-                count,
-                mut role,
-                waiting,
-                survivors,
-                votes,
-                candidates,
-            } => {
-                // 自分のロールのみにフィルターする
-                role = role.drain().filter(|(k, _)| k == self.name).collect();
-
-                Day {
-                    count,
+                // 次の夜がやってきました。
+                let next = State::Day {
+                    count: count + 1,
                     role,
-                    waiting,
+                    waiting: survivors.clone(),
+                    ready: HashSet::new(),
+                    candidates: survivors.clone(),
+                    votes: HashMap::new(),
                     survivors,
-                    votes,
-                    candidates,
-                }
+                    lovers,
+                    deaths: deaths.clone(),
+                    runoff: 0,
+                };
+                // 後追いと道連れと勝敗判定
+                *state = advance(count, next, deaths, config);
+                true
+            } else {
+                false
             }
-            Night {
-                count,
-                mut role,
-                waiting,
-                survivors,
-            } => {
-                // 自分のロールのみにフィルターする
-                role = role.drain().filter(|(k, _)| k == self.name).collect();
-
-                Night {
-                    count,
-                    role,
-                    waiting,
-                    survivors,
-                }
+        }
+        State::Revenge {
+            count,
+            waiting,
+            shots,
+            mut next,
+        } => {
+            if waiting.is_empty() {
+                // 狩人の名前順に道連れにする
+                let mut shots: Vec<_> = shots.into_iter().collect();
+                shots.sort();
+                let dead: Vec<_> = shots
+                    .into_iter()
+                    .map(|(_, name)| Death {
+                        name,
+                        cause: Cause::Revenge,
+                    })
+                    .filter(|death| kill(&mut next, death))
+                    .collect();
+                // 道連れにされた狩人が更に道連れにする場合もある
+                *state = advance(count, *next, dead, config);
+                true
+            } else {
+                false
             }
         }
+        State::End { .. } => false,
     }
 }
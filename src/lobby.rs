@@ -0,0 +1,126 @@
+use crate::{
+    master::{Master, Token},
+    request::Any,
+    state::State,
+};
+
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+use strum::IntoStaticStr;
+use thiserror::Error;
+
+/// ゲームの識別子
+pub type GameId = String;
+
+/// ロビー関連のエラー
+#[derive(Error, Debug, Serialize, IntoStaticStr)]
+#[serde(tag = "code", content = "detail", rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum Error {
+    /// 指定された識別子のゲームが存在しない場合
+    #[error("game `{0}` is not found.")]
+    GameNotFound(GameId),
+    /// 指定された識別子のゲームが既に存在する場合
+    #[error("game `{0}` already exists.")]
+    GameAlreadyExists(GameId),
+}
+
+impl Error {
+    /// エラーコードを返す。
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// 共有されるゲーム
+/// 閲覧は読み取りロック、リクエストの実行は書き込みロックで行う。
+pub type SharedMaster = Arc<RwLock<Master>>;
+
+/// 複数のゲームを識別子で管理するロビー
+/// スレッド間で共有して用いる。
+/// # Example
+/// ```
+/// use std::{sync::Arc, thread};
+/// use werewolf::{lobby::Lobby, master::Master};
+/// let lobby = Arc::new(Lobby::new());
+/// let game = lobby.create("村1".to_string(), Master::new()).unwrap();
+/// let token = game.write().unwrap().signup("たろう".to_string()).unwrap();
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         let lobby = lobby.clone();
+///         thread::spawn(move || lobby.view_state("村1", &token).unwrap())
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), lobby.view_state("村1", &token).unwrap());
+/// }
+/// ```
+#[derive(Default)]
+pub struct Lobby {
+    games: RwLock<HashMap<GameId, SharedMaster>>,
+}
+
+impl Lobby {
+    /// 空のロビーを返す。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ゲームを登録し、共有されたゲームを返す。
+    pub fn create(&self, id: GameId, master: Master) -> Result<SharedMaster, Error> {
+        let mut games = self.games.write().unwrap_or_else(PoisonError::into_inner);
+        if games.contains_key(&id) {
+            return Err(Error::GameAlreadyExists(id));
+        }
+        let master = Arc::new(RwLock::new(master));
+        games.insert(id, master.clone());
+        Ok(master)
+    }
+
+    /// 識別子からゲームを得る。
+    pub fn get(&self, id: &str) -> Result<SharedMaster, Error> {
+        let games = self.games.read().unwrap_or_else(PoisonError::into_inner);
+        games
+            .get(id)
+            .cloned()
+            .ok_or_else(|| Error::GameNotFound(id.to_owned()))
+    }
+
+    /// ゲームを削除し、削除されたゲームを返す。
+    pub fn remove(&self, id: &str) -> Result<SharedMaster, Error> {
+        let mut games = self.games.write().unwrap_or_else(PoisonError::into_inner);
+        games
+            .remove(id)
+            .ok_or_else(|| Error::GameNotFound(id.to_owned()))
+    }
+
+    /// 登録されている全てのゲームの識別子を返す。
+    pub fn ids(&self) -> Vec<GameId> {
+        let games = self.games.read().unwrap_or_else(PoisonError::into_inner);
+        games.keys().cloned().collect()
+    }
+
+    /// ゲームの状態を、そのユーザーが閲覧できる範囲にフィルターして返す。
+    /// 読み取りロックのみを取るため、他の閲覧と同時に行える。
+    pub fn view_state(&self, id: &str, token: &Token) -> Result<State, crate::Error> {
+        let game = self.get(id)?;
+        let master = game.read().unwrap_or_else(PoisonError::into_inner);
+        Ok(master.viewer(token)?.view_state())
+    }
+
+    /// ゲームに対してリクエストを実行する。
+    /// 書き込みロックを取るため、同じゲームへのリクエストは直列に処理される。
+    pub fn execute(
+        &self,
+        id: &str,
+        token: &Token,
+        req: impl Into<Any>,
+    ) -> Result<(), crate::Error> {
+        let game = self.get(id)?;
+        let mut master = game.write().unwrap_or_else(PoisonError::into_inner);
+        master.login(token)?.execute(req.into())
+    }
+}
@@ -0,0 +1,17 @@
+use std::time::SystemTime;
+
+/// 現在時刻を返す時計
+pub trait Clock: Send + Sync {
+    /// 現在時刻
+    fn now(&self) -> SystemTime;
+}
+
+/// システムの時計
+#[derive(Default, Debug, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
@@ -1,15 +1,32 @@
-use crate::role::Role;
+use crate::{
+    role::{behavior::Proposal, Role},
+    state::{Name, Phase},
+};
 
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
+use strum::IntoStaticStr;
 use thiserror::Error;
 
 /// 設定関連のエラー
-#[derive(Error, Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Error, Debug, Serialize, IntoStaticStr)]
+#[serde(tag = "code", content = "detail", rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
 pub enum Error {
     /// role_countsに記載された人数とメンバー数が一致しません。
     #[error("The number of members does not match the number of people listed in role_counts.")]
-    InvalidRoleCounts(Config),
+    InvalidRoleCounts(Box<Config>),
+}
+
+impl Error {
+    /// エラーコードを返す。
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
 }
 
 /// ゲーム設定
@@ -19,18 +36,26 @@ pub struct Config {
     pub hunter: HunterConfig,
     pub seer: SeerConfig,
     pub wolf: WolfConfig,
+    pub medium: MediumConfig,
+    pub madman: MadmanConfig,
+    pub fox: FoxConfig,
+    pub cupid: CupidConfig,
+    pub witch: WitchConfig,
+    pub mason: MasonConfig,
+    pub nekomata: NekomataConfig,
+    pub jester: JesterConfig,
+    pub vote: VoteConfig,
+    pub time_limit: TimeLimitConfig,
+    pub action: ActionConfig,
+    pub first_night: FirstNightConfig,
+    /// 独自の役職の名前と人数
+    pub custom: BTreeMap<String, usize>,
 }
 
 impl Config {
     // 与えられたロールがスキップ可能かどうか。
     pub fn skippable(&self, role: &Role) -> bool {
-        use Role::*;
-        match role {
-            Citizen => true,
-            Wolf { .. } => self.wolf.skippable,
-            Seer { .. } => self.seer.skippable,
-            Hunter { .. } => self.hunter.skippable,
-        }
+        role.behavior().skippable(self)
     }
 }
 
@@ -50,6 +75,8 @@ pub struct HunterConfig {
     pub skippable: bool,
     /// 連続して同じ人を守れるかどうか
     pub consecutive_guard: bool,
+    /// 追放または殺害された時に、生存者を一人道連れにできるかどうか
+    pub revenge: bool,
 }
 
 /// 人狼の設定
@@ -59,6 +86,82 @@ pub struct WolfConfig {
     pub count: usize,
     /// スキップできるかどうか
     pub skippable: bool,
+    /// 襲撃先の決定方法
+    pub decision: KillDecision,
+}
+
+/// 人狼たちの襲撃先の提案から、一晩に一人の襲撃先を決定する方法
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum KillDecision {
+    /// 最も多く提案された人。同数の場合はその中からランダム
+    #[default]
+    Majority,
+    /// 最後に提案された人
+    LastSubmitted,
+    /// リーダー(生存している人狼のうち名前順で最初の者)が提案した人
+    Leader,
+    /// 提案の中からランダム
+    Random,
+}
+
+impl KillDecision {
+    /// 生存している人狼たちの提案から襲撃先を決定する。
+    /// # Example
+    /// ```
+    /// use werewolf::{master::config::KillDecision, role::behavior::Proposal};
+    /// let proposal = |wolf: &str, target: Option<&str>, sequence| Proposal {
+    ///     wolf: wolf.to_string(),
+    ///     target: target.map(str::to_string),
+    ///     sequence,
+    /// };
+    /// let proposals = [
+    ///     proposal("たろう", Some("めじろ"), 1),
+    ///     proposal("はなこ", Some("ぽち"), 2),
+    ///     // 提案した後に取り消した人狼
+    ///     proposal("じろう", None, 3),
+    /// ];
+    /// let rng = &mut rand::thread_rng();
+    /// assert_eq!(
+    ///     KillDecision::LastSubmitted.decide(&proposals, rng),
+    ///     Some(&"ぽち".to_string())
+    /// );
+    /// assert_eq!(
+    ///     KillDecision::Leader.decide(&proposals, rng),
+    ///     None
+    /// );
+    /// ```
+    pub fn decide<'a>(&self, proposals: &'a [Proposal], rng: &mut impl Rng) -> Option<&'a Name> {
+        let mut targets: Vec<_> = proposals
+            .iter()
+            .filter_map(|proposal| proposal.target.as_ref())
+            .collect();
+        targets.sort();
+        match self {
+            Self::Majority => {
+                let mut freqs = BTreeMap::new();
+                for target in targets {
+                    *freqs.entry(target).or_insert(0) += 1;
+                }
+                let max_count = freqs.values().cloned().max().unwrap_or(0);
+                let tops: Vec<_> = freqs
+                    .into_iter()
+                    .filter_map(|(target, count)| (count == max_count).then_some(target))
+                    .collect();
+                tops.choose(rng).copied()
+            }
+            Self::LastSubmitted => proposals
+                .iter()
+                .filter(|proposal| proposal.target.is_some())
+                .max_by_key(|proposal| proposal.sequence)
+                .and_then(|proposal| proposal.target.as_ref()),
+            Self::Leader => proposals
+                .iter()
+                .min_by_key(|proposal| &proposal.wolf)
+                .and_then(|proposal| proposal.target.as_ref()),
+            Self::Random => targets.choose(rng).copied(),
+        }
+    }
 }
 
 /// 占い師の設定
@@ -69,3 +172,175 @@ pub struct SeerConfig {
     /// スキップできるかどうか
     pub skippable: bool,
 }
+
+/// 霊媒師の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct MediumConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// 狂人の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct MadmanConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// 妖狐の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct FoxConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// キューピッドの設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct CupidConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// 魔女の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct WitchConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// 共有者の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct MasonConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// 猫又の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct NekomataConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// 道化師の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct JesterConfig {
+    /// 人数
+    pub count: usize,
+}
+
+/// 昼の投票の設定
+///
+/// 誰も追放しないことへの票が、最多得票者の票数と同数以上であれば誰も追放しない。
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct VoteConfig {
+    /// 最多得票者が複数いた場合の処理
+    pub tie_break: TieBreak,
+    /// 投票を棄権できるかどうか
+    pub abstainable: bool,
+    /// 誰も追放しないことに投票できるかどうか
+    pub no_exile: bool,
+}
+
+/// 行動の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct ActionConfig {
+    /// 行動を確定するまで、投票や夜の行動を変更できるかどうか。
+    /// 有効な場合、場面は全員が行動を確定するか締め切りを過ぎるまで進まない。
+    pub revisable: bool,
+}
+
+/// 初日の夜の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct FirstNightConfig {
+    /// 実際に行われる最初の夜の襲撃を禁止するかどうか。人狼は襲撃せずに行動をスキップする
+    pub no_attack: bool,
+    /// 占い師に、人狼でない人をランダムに一人知らせるかどうか(お告げ)
+    pub random_white: bool,
+    /// 初日の夜を飛ばして、昼から始めるかどうか
+    pub skip: bool,
+}
+
+impl FirstNightConfig {
+    /// 実際に行われる最初の夜の周回。
+    /// 初日の夜を飛ばす場合は、最初の昼の次の夜となる。
+    pub fn count(&self) -> usize {
+        if self.skip {
+            2
+        } else {
+            0
+        }
+    }
+
+    /// 襲撃が禁止されている夜かどうか。
+    pub fn forbids_attack(&self, count: usize) -> bool {
+        self.no_attack && count == self.count()
+    }
+}
+
+/// 最多得票者が複数いた場合の処理
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TieBreak {
+    /// 一人に決まるまで決選投票を繰り返す
+    #[default]
+    Runoff,
+    /// 一度だけ決選投票を行い、なお同票であればその中からランダムに追放する
+    RunoffOnceThenRandom,
+    /// 一度だけ決選投票を行い、なお同票であれば誰も追放しない
+    RunoffOnceThenNobody,
+    /// 同票の全員を追放する
+    AllExiled,
+    /// 決選投票を指定の回数まで行い、なお同票であれば誰も追放しない
+    RunoffLimit(usize),
+}
+
+/// 制限時間の設定
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct TimeLimitConfig {
+    /// 昼の制限時間。Noneであれば無制限
+    pub day: Option<Duration>,
+    /// 夜の制限時間。Noneであれば無制限
+    pub night: Option<Duration>,
+    /// 道連れの制限時間。Noneであれば無制限。締め切りを過ぎると誰も道連れにしない
+    pub revenge: Option<Duration>,
+    /// 昼の締め切りまでに投票しなかった人の行動
+    pub day_default: DayDefault,
+    /// 夜の締め切りまでに行動しなかった人の行動
+    pub night_default: NightDefault,
+}
+
+impl TimeLimitConfig {
+    /// 場面の開始時刻から締め切りを求める。
+    pub fn deadline(&self, phase: Phase, start: SystemTime) -> Option<SystemTime> {
+        match phase {
+            Phase::Day(_) => self.day,
+            Phase::Night(_) => self.night,
+            Phase::Revenge(_) => self.revenge,
+            Phase::Waiting | Phase::End => None,
+        }
+        .map(|limit| start + limit)
+    }
+}
+
+/// 昼の締め切りまでに投票しなかった人の行動
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DayDefault {
+    /// 棄権する
+    #[default]
+    Abstain,
+    /// 候補者からランダムに投票する
+    RandomVote,
+}
+
+/// 夜の締め切りまでに行動しなかった人の行動
+#[derive(Default, Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NightDefault {
+    /// 行動をスキップする
+    #[default]
+    Skip,
+    /// 生存者からランダムに対象を選んで行動する
+    RandomAction,
+}
@@ -1,20 +1,31 @@
+pub mod clock;
 pub mod config;
+pub mod snapshot;
 pub use config::Config;
 use serde::Serialize;
+pub use snapshot::Snapshot;
 
-use super::{Name, Permission, State};
-use crate::role::{Error as RoleError, Role};
+use super::{Name, Permission, State, Viewer};
+use crate::event::Event;
+use crate::role::{behavior, Error as RoleError, Role, Team};
+use crate::state::{NightStage, Phase};
 
 use bimap::BiHashMap;
+use clock::{Clock, SystemClock};
 use config::Error as ConfigError;
-use rand::{random, seq::SliceRandom};
-use std::{cell::Cell, collections::HashSet};
-use strum::IntoEnumIterator;
+use rand::{random, seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::{
+    collections::{HashMap, HashSet},
+    time::SystemTime,
+};
+use strum::{IntoEnumIterator, IntoStaticStr};
 use thiserror::Error;
 
 /// マスター関連のエラー
-#[derive(Error, Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Error, Debug, Serialize, IntoStaticStr)]
+#[serde(tag = "code", content = "detail", rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
 pub enum Error {
     /// 登録時のユーザー名が被る場合
     #[error("display name of `{0}` is already in use.")]
@@ -27,11 +38,24 @@ pub enum Error {
     GameAlreadyStarted,
     /// 役割処理の際のエラー
     #[error("RoleError: {0}")]
+    #[serde(untagged)]
     Role(#[from] RoleError),
     /// 設定のエラー
     #[error("ConfigError: {0}")]
+    #[serde(untagged)]
     Config(#[from] ConfigError),
 }
+
+impl Error {
+    /// エラーコードを返す。
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Role(err) => err.code(),
+            Self::Config(err) => err.code(),
+            _ => self.into(),
+        }
+    }
+}
 /// トークン
 pub type Token = [u8; 32];
 
@@ -40,9 +64,19 @@ pub struct Master {
     /// トークンから表示名への辞書
     tokens: BiHashMap<Token, Name>,
     /// ゲーム設定。ゲームのルールが主。
-    config: Config,
+    pub(crate) config: Config,
     /// 状態。場面とそれに依存するデータ。
-    state: Cell<State>,
+    pub(crate) state: State,
+    /// これまでに起きた出来事の記録
+    pub(crate) log: Vec<Event>,
+    /// 乱数のシード
+    seed: u64,
+    /// 乱数生成器。トークンの発行や役職の割り当てなど全ての乱数はここから得る。
+    rng: ChaCha12Rng,
+    /// 現在の場面の締め切り
+    deadline: Option<SystemTime>,
+    /// 締め切りの判定に用いる時計
+    clock: Box<dyn Clock>,
 }
 
 impl Default for Master {
@@ -63,12 +97,75 @@ impl From<Config> for Master {
 impl Master {
     /// ユーザー待機状態のゲームマスターのインスタンスを返す。
     pub fn new() -> Self {
+        Self::with_seed(Config::default(), random())
+    }
+
+    /// 乱数のシードを指定して、ユーザー待機状態のゲームマスターのインスタンスを返す。
+    /// 同じシードと同じ操作の列からは、常に同じゲームが再現される。
+    pub fn with_seed(config: Config, seed: u64) -> Self {
         Master {
-            state: Cell::new(State::default()),
+            state: State::default(),
             tokens: BiHashMap::new(),
-            config: Config::default(),
+            config,
+            log: Vec::new(),
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            deadline: None,
+            clock: Box::new(SystemClock),
         }
     }
+
+    /// 締め切りの判定に用いる時計を差し替える。
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// シードと出来事の記録からゲームを再現する。
+    /// # Example
+    /// ```
+    /// use werewolf::{master::{config::*, Master}, request};
+    /// let config = Config {
+    ///     citizen: CitizenConfig { count: 2 },
+    ///     ..Default::default()
+    /// };
+    /// let mut master = Master::with_seed(config.clone(), 42);
+    /// let tokens = ["たろう", "はなこ"].map(|name| master.signup(name.to_string()).unwrap());
+    /// master.start().unwrap();
+    /// master.login(&tokens[0]).unwrap().execute(request::Skip()).unwrap();
+    ///
+    /// let mut replayed = Master::replay(config, master.seed(), master.log()).unwrap();
+    /// assert_eq!(replayed.log(), master.log());
+    /// assert_eq!(
+    ///     replayed.login(&tokens[1]).unwrap().view_state(),
+    ///     master.login(&tokens[1]).unwrap().view_state(),
+    /// );
+    /// ```
+    pub fn replay(config: Config, seed: u64, log: &[Event]) -> Result<Self, crate::Error> {
+        let mut master = Self::with_seed(config, seed);
+        for event in log {
+            match event {
+                Event::Signup { name } => {
+                    master.signup(name.to_owned())?;
+                }
+                Event::Transition {
+                    from: Phase::Waiting,
+                    ..
+                } => master.start()?,
+                Event::Timeout { .. } => master.timeout(),
+                Event::Request { name, request, .. } => {
+                    let token = *master
+                        .tokens
+                        .get_by_right(name)
+                        .ok_or(Error::AuthenticationFailed)?;
+                    master.login(&token)?.execute(request.to_owned())?;
+                }
+                // 自動的な場面の移行はリクエストから再現される
+                Event::Transition { .. } => (),
+            }
+        }
+        Ok(master)
+    }
     /// ユーザーを登録する
     /// # Example
     /// ```
@@ -79,12 +176,13 @@ impl Master {
     /// assert!(matches!(master.signup("たろう".to_string()), Err(NameAlreadyRegistered(_))));
     /// ```
     pub fn signup(&mut self, name: Name) -> Result<Token, Error> {
-        if let State::Waiting { .. } = self.state.get_mut() {
+        if let State::Waiting { .. } = self.state {
             if self.tokens.contains_right(&name) {
                 return Err(Error::NameAlreadyRegistered(name));
             }
-            let token: Token = random();
-            self.tokens.insert(token, name);
+            let token: Token = self.rng.gen();
+            self.tokens.insert(token, name.clone());
+            self.log.push(Event::Signup { name });
             Ok(token)
         } else {
             Err(Error::GameAlreadyStarted)
@@ -99,20 +197,28 @@ impl Master {
     /// assert!(matches!(master.login(&token), Ok(_permission)));
     /// assert!(matches!(master.login(&Default::default()), Err(AuthenticationFailed)));
     /// ```
-    pub fn login(&mut self, token: &Token) -> Result<Permission, Error> {
-        let Self {
-            state,
-            ref tokens,
-            config,
-        } = self;
-        let Some(name) = tokens.get_by_left(token) else {
-            return  Err(Error::AuthenticationFailed);
+    pub fn login(&mut self, token: &Token) -> Result<Permission<'_>, Error> {
+        let Some(name) = self.tokens.get_by_left(token).cloned() else {
+            return Err(Error::AuthenticationFailed);
         };
-        Ok(Permission {
-            name,
-            state,
-            config,
-        })
+        Ok(Permission { name, master: self })
+    }
+
+    /// トークンから閲覧用の権限を得る
+    /// # Example
+    /// ```
+    /// use werewolf::master::{Master, Error::AuthenticationFailed};
+    /// let mut master = Master::new();
+    /// let token = master.signup("たろう".to_string()).unwrap();
+    /// let (a, b) = (master.viewer(&token).unwrap(), master.viewer(&token).unwrap());
+    /// assert_eq!(a.view_state(), b.view_state());
+    /// assert!(matches!(master.viewer(&Default::default()), Err(AuthenticationFailed)));
+    /// ```
+    pub fn viewer(&self, token: &Token) -> Result<Viewer<'_>, Error> {
+        let Some(name) = self.tokens.get_by_left(token) else {
+            return Err(Error::AuthenticationFailed);
+        };
+        Ok(Viewer { name, master: self })
     }
 
     /// 開始していないゲームをスタートする。
@@ -124,40 +230,184 @@ impl Master {
     /// assert!(matches!(master.start(), Err(GameAlreadyStarted)));
     /// ```
     pub fn start(&mut self) -> Result<(), Error> {
-        if let State::Waiting { .. } = self.state.get_mut() {
-            let survivors = HashSet::from_iter(self.tokens.right_values().map(|a| a.to_owned()));
-            let role = {
+        if let State::Waiting { .. } = self.state {
+            let survivors: HashSet<_> = self.tokens.right_values().cloned().collect();
+            if let Some(kind) = self
+                .config
+                .custom
+                .keys()
+                .find(|kind| behavior::get(kind).is_none())
+            {
+                return Err(RoleError::UnknownRole(kind.to_owned()).into());
+            }
+            let mut role: HashMap<_, _> = {
+                let customs = self.config.custom.keys().map(|kind| Role::Custom {
+                    kind: kind.to_owned(),
+                    target: None,
+                });
                 let mut all_roles = Role::iter()
+                    .filter(|role| !matches!(role, Role::Custom { .. }))
+                    .chain(customs)
                     .flat_map(|role| {
-                        let count: usize = match &role {
-                            Role::Citizen => self.config.citizen.count,
-                            Role::Hunter { .. } => self.config.hunter.count,
-                            Role::Wolf { .. } => self.config.wolf.count,
-                            Role::Seer { .. } => self.config.seer.count,
-                        };
-                        std::iter::repeat(role).take(count)
+                        let count = role.behavior().count(&self.config);
+                        std::iter::repeat_n(role, count)
                     })
                     .collect::<Vec<Role>>();
                 if all_roles.len() != survivors.len() {
-                    return Err(ConfigError::InvalidRoleCounts(self.config.clone()).into());
+                    return Err(
+                        ConfigError::InvalidRoleCounts(Box::new(self.config.clone())).into(),
+                    );
                 }
-                all_roles.shuffle(&mut rand::thread_rng());
-                survivors.clone().into_iter().zip(all_roles).collect()
+                all_roles.shuffle(&mut self.rng);
+                // 再現性のため、名前順に割り当てる
+                let mut names: Vec<_> = survivors.iter().cloned().collect();
+                names.sort();
+                names.into_iter().zip(all_roles).collect()
             };
 
+            // お告げ
+            if self.config.first_night.random_white {
+                let mut names: Vec<_> = role.keys().cloned().collect();
+                names.sort();
+                // 占い師の分だけ乱数を使う
+                let seers: Vec<_> = names
+                    .iter()
+                    .filter(|name| matches!(role[*name], Role::Seer { .. }))
+                    .cloned()
+                    .collect();
+                for seer in seers.iter() {
+                    let whites: Vec<_> = names
+                        .iter()
+                        .filter(|name| *name != seer && role[*name].divined_as() == Team::Citizen)
+                        .collect();
+                    let Some(white) = whites.choose(&mut self.rng).map(|&white| white.clone())
+                    else {
+                        continue;
+                    };
+                    if let Some(Role::Seer { prediction, .. }) = role.get_mut(seer) {
+                        prediction.insert(white, Team::Citizen);
+                    }
+                }
+            }
+
             // stateの初期化。
-            self.state = Cell::new(State::Night {
-                count: 0,
-                role,
-                waiting: survivors.clone(),
-                survivors,
+            let first_night = &self.config.first_night;
+            self.state = if first_night.skip {
+                State::Day {
+                    count: 1,
+                    role,
+                    waiting: survivors.clone(),
+                    ready: HashSet::new(),
+                    candidates: survivors.clone(),
+                    lovers: Vec::new(),
+                    votes: HashMap::new(),
+                    survivors,
+                    deaths: Vec::new(),
+                    runoff: 0,
+                }
+            } else {
+                // 襲撃が禁止されていても、人狼が誰であるかを伏せるため全員の行動を待つ
+                State::Night {
+                    count: 0,
+                    role,
+                    waiting: survivors.clone(),
+                    ready: HashSet::new(),
+                    survivors,
+                    lovers: Vec::new(),
+                    deaths: Vec::new(),
+                    tally: HashMap::new(),
+                    no_exile: 0,
+                    tie_break: None,
+                    stage: Default::default(),
+                }
+            };
+            let phase = self.state.phase();
+            self.log.push(Event::Transition {
+                from: Phase::Waiting,
+                to: self.state.clone(),
             });
+            self.deadline = self.config.time_limit.deadline(phase, self.clock.now());
             Ok(())
         } else {
             Err(Error::GameAlreadyStarted)
         }
     }
 
+    /// 全員の行動が揃っていれば場面を解決し、次の場面へ移行する。
+    pub(crate) fn proceed(&mut self, from: Phase) {
+        let state = &mut self.state;
+        if crate::resolve(state, &mut self.config, &mut self.rng) {
+            let phase = state.phase();
+            self.log.push(Event::Transition {
+                from,
+                to: state.clone(),
+            });
+            // 魔女の行動する段階は伏せられているため、夜の締め切りをそのまま引き継ぐ
+            let witch = matches!(
+                state,
+                State::Night {
+                    stage: NightStage::Witch { .. },
+                    ..
+                }
+            );
+            if !witch {
+                self.deadline = self.config.time_limit.deadline(phase, self.clock.now());
+            }
+        }
+    }
+
+    /// 現在の場面の締め切りを過ぎていれば、行動していない人に既定の行動をとらせて場面を進める。
+    /// 締め切りを過ぎていた場合はtrueを返す。
+    /// # Example
+    /// ```
+    /// use std::time::{Duration, SystemTime};
+    /// use werewolf::master::{clock::Clock, config::*, Master};
+    /// struct Later;
+    /// impl Clock for Later {
+    ///     fn now(&self) -> SystemTime {
+    ///         SystemTime::now() + Duration::from_secs(3600)
+    ///     }
+    /// }
+    /// let mut master = Master::from(Config {
+    ///     citizen: CitizenConfig { count: 2 },
+    ///     time_limit: TimeLimitConfig {
+    ///         night: Some(Duration::from_secs(60)),
+    ///         ..Default::default()
+    ///     },
+    ///     ..Default::default()
+    /// });
+    /// master.signup("たろう".to_string()).unwrap();
+    /// master.signup("はなこ".to_string()).unwrap();
+    /// master.start().unwrap();
+    /// assert!(!master.tick());
+    /// let mut master = master.with_clock(Later);
+    /// assert!(master.tick());
+    /// ```
+    pub fn tick(&mut self) -> bool {
+        match self.deadline {
+            Some(deadline) if self.clock.now() >= deadline => {
+                self.timeout();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 行動していない人に既定の行動をとらせて場面を進める。
+    fn timeout(&mut self) {
+        let state = &mut self.state;
+        let phase = state.phase();
+        crate::apply_defaults(state, &self.config, &mut self.rng);
+        self.log.push(Event::Timeout { phase });
+        self.proceed(phase);
+    }
+
+    /// 現在の場面の締め切りを得る。
+    /// 魔女の行動する段階でも、夜の締め切りのまま変わらない。
+    pub fn deadline(&self) -> Option<SystemTime> {
+        self.deadline
+    }
+
     /// マスターに参加中の全てのプレイヤー名の一覧を取得する
     pub fn players(&self) -> HashSet<&Name> {
         self.tokens.right_values().collect()
@@ -168,6 +418,17 @@ impl Master {
         &self.config
     }
 
+    /// 全ての出来事の記録を得る。
+    /// プレイヤーに公開する場合は`Permission::history`を用いること。
+    pub fn log(&self) -> &[Event] {
+        &self.log
+    }
+
+    /// 乱数のシードを得る
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     /// トークンから名前を得る
     pub fn get_name(&self, token: &Token) -> Option<&Name> {
         self.tokens.get_by_left(token)
@@ -0,0 +1,200 @@
+use super::{clock::SystemClock, Config, Master, Token};
+use crate::{
+    event::Event,
+    role::{behavior, Error as RoleError},
+    state::{Name, State},
+};
+
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+use strum::IntoStaticStr;
+use thiserror::Error;
+
+/// 現在のスナップショットのスキーマのバージョン
+///
+/// 異なるバージョンのスナップショットは互換性がないものとして復元を拒否する。
+/// `Config`や`State`、`Event`など、スナップショットに含まれる型の形を変更した場合は必ず上げること。
+pub const VERSION: u32 = 6;
+
+/// スナップショット関連のエラー
+#[derive(Error, Debug, Serialize, IntoStaticStr)]
+#[serde(tag = "code", content = "detail", rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum Error {
+    /// スナップショットのバージョンに対応していない場合
+    #[error("snapshot version {found} is not supported (expected {expected}).")]
+    UnsupportedVersion { found: u32, expected: u32 },
+    /// 役職処理の際のエラー
+    #[error("RoleError: {0}")]
+    #[serde(untagged)]
+    Role(#[from] RoleError),
+}
+
+impl Error {
+    /// エラーコードを返す。
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Role(err) => err.code(),
+            _ => self.into(),
+        }
+    }
+}
+
+/// ゲームマスターの保存用の表現
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Snapshot {
+    /// スキーマのバージョン
+    pub version: u32,
+    /// トークンと表示名の組
+    tokens: Vec<(Token, Name)>,
+    /// ゲーム設定
+    config: Config,
+    /// 状態
+    state: State,
+    /// 出来事の記録
+    log: Vec<Event>,
+    /// 乱数のシード
+    seed: u64,
+    /// 乱数生成器の内部状態
+    rng: ChaCha12Rng,
+    /// 現在の場面の締め切り
+    deadline: Option<SystemTime>,
+}
+
+impl Master {
+    /// 進行中のゲームを保存用の表現に変換する。
+    /// # Example
+    /// ```
+    /// use werewolf::master::{Master, Snapshot};
+    /// let mut master = Master::new();
+    /// let token = master.signup("たろう".to_string()).unwrap();
+    /// let json = serde_json::to_string(&master.snapshot()).unwrap();
+    /// let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+    /// let mut restored = Master::restore(snapshot).unwrap();
+    /// assert_eq!(restored.get_name(&token), Some(&"たろう".to_string()));
+    /// assert_eq!(restored.log(), master.log());
+    /// ```
+    pub fn snapshot(&self) -> Snapshot {
+        let mut tokens: Vec<_> = self
+            .tokens
+            .iter()
+            .map(|(token, name)| (*token, name.to_owned()))
+            .collect();
+        tokens.sort_by(|(_, a), (_, b)| a.cmp(b));
+        Snapshot {
+            version: VERSION,
+            tokens,
+            config: self.config.clone(),
+            state: self.state.clone(),
+            log: self.log.clone(),
+            seed: self.seed,
+            rng: self.rng.clone(),
+            deadline: self.deadline,
+        }
+    }
+
+    /// 保存用の表現からゲームを復元する。
+    /// 時計はシステムの時計となるため、必要に応じて`Master::with_clock`で差し替えること。
+    /// 独自の役職は、開始時と同じく全て登録されている必要がある。
+    /// # Example
+    /// ```
+    /// use werewolf::master::{config::*, snapshot, Master, Snapshot};
+    /// let mut master = Master::from(Config {
+    ///     citizen: CitizenConfig { count: 2 },
+    ///     wolf: WolfConfig { count: 1, ..Default::default() },
+    ///     ..Default::default()
+    /// });
+    /// let tokens: Vec<_> = ["たろう", "はなこ", "めじろ"]
+    ///     .iter()
+    ///     .map(|name| master.signup(name.to_string()).unwrap())
+    ///     .collect();
+    /// master.start().unwrap();
+    ///
+    /// // 進行中のゲームも、保存して復元すると同じ状態になる
+    /// let json = serde_json::to_value(master.snapshot()).unwrap();
+    /// let restored = Master::restore(serde_json::from_value(json.clone()).unwrap()).unwrap();
+    /// assert_eq!(restored.log(), master.log());
+    /// assert_eq!(restored.config(), master.config());
+    /// for token in tokens.iter() {
+    ///     assert_eq!(
+    ///         restored.viewer(token).unwrap().view_state(),
+    ///         master.viewer(token).unwrap().view_state(),
+    ///     );
+    /// }
+    ///
+    /// // バージョンの異なるスナップショットは復元できない
+    /// let mut old = json;
+    /// old["version"] = serde_json::json!(snapshot::VERSION - 1);
+    /// let old: Snapshot = serde_json::from_value(old).unwrap();
+    /// assert!(matches!(
+    ///     Master::restore(old),
+    ///     Err(snapshot::Error::UnsupportedVersion { .. })
+    /// ));
+    /// ```
+    ///
+    /// 登録されていない独自の役職を含むスナップショットは復元できない。
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use werewolf::{
+    ///     master::{config::*, snapshot, Master},
+    ///     role,
+    /// };
+    /// let master = Master::from(Config {
+    ///     custom: BTreeMap::from([("ghost".to_string(), 1)]),
+    ///     ..Default::default()
+    /// });
+    /// assert!(matches!(
+    ///     Master::restore(master.snapshot()),
+    ///     Err(snapshot::Error::Role(role::Error::UnknownRole(_)))
+    /// ));
+    /// ```
+    pub fn restore(snapshot: Snapshot) -> Result<Self, Error> {
+        let Snapshot {
+            version,
+            tokens,
+            config,
+            state,
+            log,
+            seed,
+            rng,
+            deadline,
+        } = snapshot;
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion {
+                found: version,
+                expected: VERSION,
+            });
+        }
+        // 登録されていない役職は、市民として振る舞ってしまう
+        if let Some(kind) = config
+            .custom
+            .keys()
+            .find(|kind| behavior::get(kind).is_none())
+        {
+            return Err(RoleError::UnknownRole(kind.to_owned()).into());
+        }
+        Ok(Master {
+            tokens: tokens.into_iter().collect(),
+            config,
+            state,
+            log,
+            seed,
+            rng,
+            deadline,
+            clock: Box::new(SystemClock),
+        })
+    }
+}
+
+impl Serialize for Master {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.snapshot().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Master {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Master::restore(Snapshot::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
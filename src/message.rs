@@ -0,0 +1,178 @@
+use crate::{
+    lobby,
+    master::{self, config, snapshot},
+    request, role, Error,
+};
+
+/// エラーメッセージのカタログ
+/// 独自の言語や文言で表示したい場合はこのトレイトを実装する。
+pub trait Catalog {
+    /// マスター関連のエラーのメッセージ
+    fn master(&self, err: &master::Error) -> String;
+    /// リクエスト処理時のエラーのメッセージ
+    fn request(&self, err: &request::Error) -> String;
+    /// 役職関連のエラーのメッセージ
+    fn role(&self, err: &role::Error) -> String;
+    /// 設定関連のエラーのメッセージ
+    fn config(&self, err: &config::Error) -> String;
+    /// スナップショット関連のエラーのメッセージ
+    fn snapshot(&self, err: &snapshot::Error) -> String;
+    /// ロビー関連のエラーのメッセージ
+    fn lobby(&self, err: &lobby::Error) -> String;
+}
+
+/// カタログを用いてメッセージに変換できるエラー
+pub trait Localize {
+    /// カタログからメッセージを得る
+    fn message(&self, catalog: &impl Catalog) -> String;
+}
+
+impl Localize for Error {
+    fn message(&self, catalog: &impl Catalog) -> String {
+        match self {
+            Error::Auth(err) => err.message(catalog),
+            Error::RequestFailed(err) => err.message(catalog),
+            Error::Lobby(err) => err.message(catalog),
+        }
+    }
+}
+
+impl Localize for master::Error {
+    fn message(&self, catalog: &impl Catalog) -> String {
+        match self {
+            master::Error::Role(err) => err.message(catalog),
+            master::Error::Config(err) => err.message(catalog),
+            _ => catalog.master(self),
+        }
+    }
+}
+
+impl Localize for request::Error {
+    fn message(&self, catalog: &impl Catalog) -> String {
+        catalog.request(self)
+    }
+}
+
+impl Localize for role::Error {
+    fn message(&self, catalog: &impl Catalog) -> String {
+        catalog.role(self)
+    }
+}
+
+impl Localize for config::Error {
+    fn message(&self, catalog: &impl Catalog) -> String {
+        catalog.config(self)
+    }
+}
+
+impl Localize for snapshot::Error {
+    fn message(&self, catalog: &impl Catalog) -> String {
+        match self {
+            snapshot::Error::Role(err) => err.message(catalog),
+            _ => catalog.snapshot(self),
+        }
+    }
+}
+
+impl Localize for lobby::Error {
+    fn message(&self, catalog: &impl Catalog) -> String {
+        catalog.lobby(self)
+    }
+}
+
+/// 英語のカタログ
+#[derive(Default, Debug, Clone, Copy)]
+pub struct English;
+
+impl Catalog for English {
+    fn master(&self, err: &master::Error) -> String {
+        err.to_string()
+    }
+    fn request(&self, err: &request::Error) -> String {
+        err.to_string()
+    }
+    fn role(&self, err: &role::Error) -> String {
+        err.to_string()
+    }
+    fn config(&self, err: &config::Error) -> String {
+        err.to_string()
+    }
+    fn snapshot(&self, err: &snapshot::Error) -> String {
+        err.to_string()
+    }
+    fn lobby(&self, err: &lobby::Error) -> String {
+        err.to_string()
+    }
+}
+
+/// 日本語のカタログ
+/// # Example
+/// ```
+/// use werewolf::{master::{Error, Master}, message::{Japanese, Localize}};
+/// let mut master = Master::new();
+/// master.signup("たろう".to_string()).unwrap();
+/// let err = master.signup("たろう".to_string()).unwrap_err();
+/// assert_eq!(err.message(&Japanese), "表示名「たろう」は既に使われています。");
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Japanese;
+
+impl Catalog for Japanese {
+    fn master(&self, err: &master::Error) -> String {
+        use master::Error::*;
+        match err {
+            NameAlreadyRegistered(name) => format!("表示名「{name}」は既に使われています。"),
+            AuthenticationFailed => "認証に失敗しました。".to_string(),
+            GameAlreadyStarted => "ゲームは既に始まっています。".to_string(),
+            Role(err) => self.role(err),
+            Config(err) => self.config(err),
+        }
+    }
+    fn request(&self, err: &request::Error) -> String {
+        use request::Error::*;
+        match err {
+            InvalidState { found, .. } => {
+                format!("現在の場面({found:?})ではこのリクエストは行えません。")
+            }
+            InvalidRole { found, .. } => {
+                format!("あなたの役職({found:?})ではこのリクエストは行えません。")
+            }
+            InvalidTarget(target) => format!("「{target}」を対象にすることはできません。"),
+            SurvivorsOnly => "このリクエストは生存者のみが行えます。".to_string(),
+            MultipleActions => "この場面では既に行動しています。".to_string(),
+            ActionRequired => "行動を確定する前に行動してください。".to_string(),
+            Disallowed => "このリクエストはゲーム設定で許可されていません。".to_string(),
+        }
+    }
+    fn role(&self, err: &role::Error) -> String {
+        use role::Error::*;
+        match err {
+            UnknownRole(name) => format!("役職「{name}」は存在しません。"),
+            UnsupportedAction(name) => {
+                format!("独自の役職「{name}」は、夜に`act`以外の行動をとれません。")
+            }
+        }
+    }
+    fn config(&self, err: &config::Error) -> String {
+        use config::Error::*;
+        match err {
+            InvalidRoleCounts(_) => "参加者の人数と役職の人数の合計が一致しません。".to_string(),
+        }
+    }
+    fn snapshot(&self, err: &snapshot::Error) -> String {
+        use snapshot::Error::*;
+        match err {
+            UnsupportedVersion { found, expected } => {
+                format!("スナップショットのバージョン{found}には対応していません(対応するバージョンは{expected})。")
+            }
+            Role(err) => self.role(err),
+        }
+    }
+    fn lobby(&self, err: &lobby::Error) -> String {
+        use lobby::Error::*;
+        match err {
+            GameNotFound(id) => format!("ゲーム「{id}」は存在しません。"),
+            GameAlreadyExists(id) => format!("ゲーム「{id}」は既に存在します。"),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::*;
+
+use std::{cell::RefCell, collections::HashSet};
+use strum::IntoEnumIterator;
+
+/// 実行可能なリクエスト
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Action {
+    /// リクエストの種類
+    pub kind: Kind,
+    /// 対象を指定する場合に、選ぶことのできる対象
+    pub targets: HashSet<Name>,
+    /// 対象を指定せずに実行できるかどうか。
+    /// 投票の場合は、誰も追放しないことに投票できるかどうか。
+    pub untargeted: bool,
+}
+
+impl Kind {
+    /// 対象を指定したリクエストを作る。対象を指定しない種類であればNoneを返す。
+    pub fn with_target(self, target: Name) -> Option<Any> {
+        match self {
+            Kind::Vote => Some(
+                Vote {
+                    target: Some(target),
+                }
+                .into(),
+            ),
+            Kind::Kill => Some(Kill { target }.into()),
+            Kind::Divine => Some(Divine { target }.into()),
+            Kind::Guard => Some(Guard { target }.into()),
+            Kind::Shoot => Some(Shoot { target }.into()),
+            Kind::Act => Some(Act { target }.into()),
+            // 毒の薬を使う
+            Kind::Potion => Some(
+                Potion {
+                    heal: false,
+                    poison: Some(target),
+                }
+                .into(),
+            ),
+            // 二人を選ぶリクエスト
+            Kind::Bind => None,
+            Kind::UpdateConfig | Kind::Abstain | Kind::Skip | Kind::Ready => None,
+        }
+    }
+
+    /// 対象を指定しないリクエストを作る。対象が必須の種類であればNoneを返す。
+    fn without_target(self, config: &Config) -> Option<Any> {
+        match self {
+            Kind::UpdateConfig => Some(
+                UpdateConfig {
+                    config: config.clone(),
+                }
+                .into(),
+            ),
+            Kind::Vote => Some(Vote { target: None }.into()),
+            Kind::Abstain => Some(Abstain().into()),
+            Kind::Skip => Some(Skip().into()),
+            Kind::Ready => Some(Ready().into()),
+            // 治療の薬を使う
+            Kind::Potion => Some(
+                Potion {
+                    heal: true,
+                    poison: None,
+                }
+                .into(),
+            ),
+            Kind::Kill | Kind::Divine | Kind::Guard | Kind::Shoot | Kind::Act | Kind::Bind => None,
+        }
+    }
+}
+
+/// 現在の場面と役職で実行可能なリクエストと、その対象の一覧を返す。
+/// 実際にリクエストを試行して判定するため、`Request::modify`と常に一致する。
+/// 試行は一つの複製に対して行い、複製しなおすのはリクエストが受理された時のみとする。
+pub fn available_actions(name: &Name, state: &State, config: &Config) -> Vec<Action> {
+    // 道連れの場面では、その後の場面の参加者を対象とする
+    let current = match state {
+        State::Revenge { next, .. } => &**next,
+        state => state,
+    };
+    let names: Vec<Name> = match current {
+        State::Day { role, .. } | State::Night { role, .. } => role.keys().cloned().collect(),
+        State::Waiting { .. } | State::Revenge { .. } | State::End { .. } => Vec::new(),
+    };
+    // 失敗したリクエストは状態を変更しないため、複製を使い回せる
+    let scratch = RefCell::new(state.clone());
+    let accepts = |req: Any| {
+        let mut scratch = scratch.borrow_mut();
+        let accepted = req.modify(name, &mut scratch, config).is_ok();
+        if accepted {
+            scratch.clone_from(state);
+        }
+        accepted
+    };
+    Kind::iter()
+        .filter_map(|kind| {
+            let targets: HashSet<_> = names
+                .iter()
+                .filter(|&target| match kind {
+                    // 二人を選ぶ場合は、組になれる相手がいる人を対象とする
+                    Kind::Bind => names.iter().any(|other| {
+                        accepts(
+                            Bind {
+                                lovers: (target.to_owned(), other.to_owned()),
+                            }
+                            .into(),
+                        )
+                    }),
+                    kind => kind.with_target(target.to_owned()).is_some_and(accepts),
+                })
+                .cloned()
+                .collect();
+            let untargeted = kind.without_target(config).is_some_and(accepts);
+            (!targets.is_empty() || untargeted).then_some(Action {
+                kind,
+                targets,
+                untargeted,
+            })
+        })
+        .collect()
+}
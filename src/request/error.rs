@@ -1,18 +1,22 @@
-use crate::{role::Role, state::State};
+use crate::{role::RoleKind, state::Phase};
 
 use serde::Serialize;
+use strum::IntoStaticStr;
 use thiserror::Error;
 
 /// リクエスト処理時のエラー
-#[derive(Error, Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Error, Debug, Serialize, IntoStaticStr)]
+#[serde(tag = "code", content = "detail", rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
 pub enum Error {
     /// 場面が適切でない場合
+    /// 他のプレイヤーの情報を含まないよう、場面の種類のみを持つ
     #[error("invalid State (found {found:?}, expected pattern {expected:?})")]
-    InvalidState { found: Box<State>, expected: String },
+    InvalidState { found: Phase, expected: String },
     /// 役職が適切でない場合
+    /// 役職の持つ情報を含まないよう、役職の種類のみを持つ
     #[error("invalid Role (found {found:?}, expected pattern {expected:?})")]
-    InvalidRole { found: Box<Role>, expected: String },
+    InvalidRole { found: RoleKind, expected: String },
     /// ターゲットが適切でない場合
     #[error("you cannot request about `{0}`.")]
     InvalidTarget(String),
@@ -22,4 +26,17 @@ pub enum Error {
     /// 場面あたりのリクエスト回数を超過した場合
     #[error("cannot act more than once")]
     MultipleActions,
+    /// 行動する前に行動を確定しようとした場合
+    #[error("you must act before getting ready.")]
+    ActionRequired,
+    /// ゲーム設定で許可されていないリクエストの場合
+    #[error("this request is not allowed by the game config.")]
+    Disallowed,
+}
+
+impl Error {
+    /// エラーコードを返す。
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
 }
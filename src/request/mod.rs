@@ -1,13 +1,18 @@
+mod action;
 mod error;
 
 use super::{Name, State};
+use crate::state::{NightStage, Phase};
 use crate::{
     master::Config,
-    role::{Role, Team},
+    role::{behavior, Role, RoleKind},
 };
+pub use action::{available_actions, Action};
 pub use error::Error;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use strum::{EnumDiscriminants, EnumIter};
 
 macro_rules! _creator {
     (
@@ -25,6 +30,14 @@ macro_rules! _creator {
             )*
         }
 
+        $(
+            impl From<$name> for $EnumName {
+                fn from(item: $name) -> Self {
+                    $EnumName::$name(item)
+                }
+            }
+        )*
+
         impl Request<'_> for $EnumName {
             fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
                 match self {
@@ -39,15 +52,26 @@ macro_rules! _creator {
 
 _creator! {
     /// リクエストのうちのいずれか
-    #[derive(Serialize, Deserialize)]
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, EnumDiscriminants)]
     #[serde(rename_all = "camelCase")]
+    #[strum_discriminants(
+        name(Kind),
+        derive(Serialize, Deserialize, Hash, EnumIter),
+        serde(rename_all = "camelCase")
+    )]
     pub enum Any {
         UpdateConfig,
         Vote,
+        Abstain,
         Kill,
         Divine,
         Guard,
         Skip,
+        Ready,
+        Shoot,
+        Act,
+        Bind,
+        Potion,
     }
 }
 
@@ -55,9 +79,10 @@ _creator! {
 macro_rules! assert_state {
     ($expected: pat, $state: expr) => {
         let $expected = $state else {
-            return Err(
-                Error::InvalidState { found: Box::new($state.to_owned()), expected: stringify!($expected).to_owned() }
-            );
+            return Err(Error::InvalidState {
+                found: $state.phase(),
+                expected: stringify!($expected).to_owned(),
+            });
         };
     };
 }
@@ -66,20 +91,47 @@ macro_rules! assert_role {
     ($expected:pat, $role: expr) => {
         let $expected = $role else {
             return Err(Error::InvalidRole {
-                found: Box::new($role.to_owned()), expected: stringify!($expected).to_owned(),
+                found: RoleKind::from(&*$role),
+                expected: stringify!($expected).to_owned(),
             });
         };
     };
 }
 
+/// 行動を受け付けられるかの確認をする。
+/// 行動を変更できる場合は、行動を確定するまで受け付ける。
+fn check_pending(
+    name: &Name,
+    waiting: &HashSet<Name>,
+    ready: &HashSet<Name>,
+    config: &Config,
+) -> Result<(), Error> {
+    let pending = if config.action.revisable {
+        !ready.contains(name)
+    } else {
+        waiting.contains(name)
+    };
+    if pending {
+        Ok(())
+    } else {
+        Err(Error::MultipleActions)
+    }
+}
+
 /// リクエストが満たすべきトレイト要件
-pub trait Request<'req>: Serialize + Deserialize<'req> {
-    /// リクエストの挙動を規定する動作
+pub trait Request<'req>: Serialize + Deserialize<'req> + Into<Any> + Clone {
+    /// リクエストの挙動を規定する動作。
+    /// 失敗する場合は、状態を変更する前にエラーを返さなければならない。
     fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error>;
+
+    /// 状態を変更せずに、`modify`が失敗するかどうかを検査する。
+    fn validate(&self, name: &Name, state: &State, config: &Config) -> Result<(), Error> {
+        self.clone().modify(name, &mut state.clone(), config)
+    }
 }
 
 /// 待機中にゲーム設定を更新する
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct UpdateConfig {
     /// 新しいゲーム設定
     pub config: Config,
@@ -94,17 +146,18 @@ impl Request<'_> for UpdateConfig {
 }
 
 /// 昼に通報者に投票する
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Vote {
-    /// 投票先
-    pub target: Name,
+    /// 投票先。Noneは誰も追放しないことへの投票
+    pub target: Option<Name>,
 }
 
 impl Request<'_> for Vote {
-    fn modify(self, name: &Name, state: &mut State, _: &Config) -> Result<(), Error> {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
         assert_state!(
             State::Day {
                 waiting,
+                ready,
                 survivors,
                 votes,
                 candidates,
@@ -112,43 +165,92 @@ impl Request<'_> for Vote {
             },
             state
         );
-        if !candidates.contains(&self.target) {
-            return Err(Error::InvalidTarget(self.target));
+        match &self.target {
+            Some(target) if !candidates.contains(target) => {
+                return Err(Error::InvalidTarget(target.to_owned()));
+            }
+            None if !config.vote.no_exile => return Err(Error::Disallowed),
+            _ => (),
         }
         if !survivors.contains(name) {
             return Err(Error::SurvivorsOnly);
         }
-        if !waiting.contains(name) {
-            return Err(Error::MultipleActions);
-        }
+        check_pending(name, waiting, ready, config)?;
         votes.insert(name.to_owned(), self.target);
         waiting.remove(name);
         Ok(())
     }
 }
 
+/// 昼の投票を棄権する
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Abstain();
+
+impl Request<'_> for Abstain {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
+        assert_state!(
+            State::Day {
+                waiting,
+                ready,
+                survivors,
+                votes,
+                ..
+            },
+            state
+        );
+        if !config.vote.abstainable {
+            return Err(Error::Disallowed);
+        }
+        if !survivors.contains(name) {
+            return Err(Error::SurvivorsOnly);
+        }
+        check_pending(name, waiting, ready, config)?;
+        votes.remove(name);
+        waiting.remove(name);
+        Ok(())
+    }
+}
+
 /// 夜に住民を殺害する
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Kill {
     /// 殺害先
     pub target: Name,
 }
 
 impl Request<'_> for Kill {
-    fn modify(self, name: &Name, state: &mut State, _: &Config) -> Result<(), Error> {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
         assert_state!(
             State::Night {
+                count,
                 role,
                 waiting,
+                ready,
                 survivors,
                 ..
             },
             state
         );
-        assert_role!(Role::Wolf { ref mut killing }, role.get_mut(name).unwrap());
-        if !waiting.contains(name) {
-            return Err(Error::MultipleActions);
+        // 人狼たちの中で最後の提案となる順番
+        let next = role
+            .values()
+            .filter_map(|role| match role {
+                Role::Wolf { sequence, .. } => Some(sequence + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        assert_role!(
+            Role::Wolf {
+                ref mut killing,
+                ref mut sequence
+            },
+            role.get_mut(name).unwrap()
+        );
+        if config.first_night.forbids_attack(*count) {
+            return Err(Error::Disallowed);
         }
+        check_pending(name, waiting, ready, config)?;
         if !survivors.contains(name) {
             return Err(Error::SurvivorsOnly);
         }
@@ -156,52 +258,60 @@ impl Request<'_> for Kill {
             return Err(Error::InvalidTarget(self.target));
         }
         *killing = Some(self.target);
+        *sequence = next;
         waiting.remove(name);
         Ok(())
     }
 }
 
 /// 夜に住民を占う
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Divine {
     /// 占い先
     pub target: Name,
 }
 
 impl Request<'_> for Divine {
-    fn modify(self, name: &Name, state: &mut State, _: &Config) -> Result<(), Error> {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
         assert_state!(
             State::Night {
                 role,
                 waiting,
+                ready,
                 survivors,
                 ..
             },
             state
         );
-        let target_is_wolf = matches!(role.get(&self.target).unwrap(), Role::Wolf { .. });
-        assert_role!(Role::Seer { prediction }, role.get_mut(name).unwrap());
-        if !waiting.contains(name) {
-            return Err(Error::MultipleActions);
-        }
+        let Some(team) = role.get(&self.target).map(Role::divined_as) else {
+            return Err(Error::InvalidTarget(self.target));
+        };
+        assert_role!(
+            Role::Seer {
+                prediction,
+                divining
+            },
+            role.get_mut(name).unwrap()
+        );
+        check_pending(name, waiting, ready, config)?;
         if !survivors.contains(name) {
             return Err(Error::SurvivorsOnly);
         }
-        prediction.insert(
-            self.target,
-            if target_is_wolf {
-                Team::Wolf
-            } else {
-                Team::Citizen
-            },
-        );
+        if !survivors.contains(&self.target) || name == &self.target {
+            return Err(Error::InvalidTarget(self.target));
+        }
+        // 行動を変更できる場合は、結果を夜が明けるまで伏せる
+        if !config.action.revisable {
+            prediction.insert(self.target.clone(), team);
+        }
+        *divining = Some(self.target);
         waiting.remove(name);
         Ok(())
     }
 }
 
 /// 夜に住民を防護する
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Guard {
     /// 防護先
     pub target: Name,
@@ -213,15 +323,20 @@ impl Request<'_> for Guard {
             State::Night {
                 role,
                 waiting,
+                ready,
                 survivors,
                 ..
             },
             state
         );
-        assert_role!(Role::Hunter { ref mut guarding }, role.get_mut(name).unwrap());
-        if !waiting.contains(name) {
-            return Err(Error::MultipleActions);
-        }
+        assert_role!(
+            Role::Hunter {
+                ref mut guarding,
+                ref guarded
+            },
+            role.get_mut(name).unwrap()
+        );
+        check_pending(name, waiting, ready, config)?;
         if !survivors.contains(name) {
             return Err(Error::SurvivorsOnly);
         }
@@ -229,8 +344,8 @@ impl Request<'_> for Guard {
             return Err(Error::InvalidTarget(self.target));
         }
         if config.hunter.consecutive_guard {
-            if let Some(guarding) = guarding {
-                if guarding == &self.target {
+            if let Some(guarded) = guarded {
+                if guarded == &self.target {
                     return Err(Error::InvalidTarget(self.target));
                 }
             }
@@ -242,15 +357,17 @@ impl Request<'_> for Guard {
 }
 
 /// 夜の行動をスキップする
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct Skip();
 
 impl Request<'_> for Skip {
     fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
         assert_state!(
             State::Night {
+                count,
                 role,
                 waiting,
+                ready,
                 survivors,
                 ..
             },
@@ -258,9 +375,12 @@ impl Request<'_> for Skip {
         );
         {
             let role = role.get(name).unwrap();
-            if !config.skippable(role) {
+            // 襲撃が禁止されている夜は、人狼もスキップする
+            let forbidden = config.first_night.forbids_attack(*count)
+                && role.behavior().night_action() == Some(Kind::Kill);
+            if !config.skippable(role) && !forbidden {
                 return Err(Error::InvalidRole {
-                    found: Box::new(role.to_owned()),
+                    found: role.into(),
                     expected: stringify!(!config.skippable_roles).to_owned(),
                 });
             }
@@ -268,17 +388,251 @@ impl Request<'_> for Skip {
         if !survivors.contains(name) {
             return Err(Error::SurvivorsOnly);
         }
+        check_pending(name, waiting, ready, config)?;
+        role.get_mut(name).unwrap().clear_target();
+        waiting.remove(name);
+        Ok(())
+    }
+}
+
+/// 行動を確定する。
+/// 行動を変更できる場合のみ有効で、確定した後は行動を変更できない。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Ready();
+
+impl Request<'_> for Ready {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
+        assert_state!(
+            (State::Day {
+                waiting,
+                ready,
+                survivors,
+                ..
+            } | State::Night {
+                waiting,
+                ready,
+                survivors,
+                ..
+            }),
+            state
+        );
+        if !config.action.revisable {
+            return Err(Error::Disallowed);
+        }
+        if !survivors.contains(name) {
+            return Err(Error::SurvivorsOnly);
+        }
+        if ready.contains(name) {
+            return Err(Error::MultipleActions);
+        }
+        if waiting.contains(name) {
+            return Err(Error::ActionRequired);
+        }
+        ready.insert(name.to_owned());
+        Ok(())
+    }
+}
+
+/// 死亡した狩人が道連れにする人を選ぶ
+///
+/// 追放された場合も、襲撃された場合も道連れの場面となり、道連れの後に勝敗を判定する。
+/// 締め切りを過ぎると、誰も道連れにしない。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Shoot {
+    /// 道連れ先
+    pub target: Name,
+}
+
+impl Request<'_> for Shoot {
+    fn modify(self, name: &Name, state: &mut State, _: &Config) -> Result<(), Error> {
+        assert_state!(
+            State::Revenge {
+                waiting,
+                shots,
+                next,
+                ..
+            },
+            state
+        );
+        assert_state!(
+            (State::Day {
+                role,
+                survivors,
+                ..
+            } | State::Night {
+                role,
+                survivors,
+                ..
+            }),
+            &**next
+        );
+        assert_role!(Role::Hunter { .. }, role.get(name).unwrap());
         if !waiting.contains(name) {
             return Err(Error::MultipleActions);
         }
-        {
-            use Role::*;
-            if let Wolf { killing: target } | Hunter { guarding: target } =
-                role.get_mut(name).unwrap()
-            {
-                *target = None;
+        if !survivors.contains(&self.target) {
+            return Err(Error::InvalidTarget(self.target));
+        }
+        shots.insert(name.to_owned(), self.target);
+        waiting.remove(name);
+        Ok(())
+    }
+}
+
+/// 独自の役職が夜に行動する
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Act {
+    /// 行動の対象
+    pub target: Name,
+}
+
+impl Request<'_> for Act {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
+        assert_state!(
+            State::Night {
+                role,
+                waiting,
+                ready,
+                survivors,
+                ..
+            },
+            state
+        );
+        assert_role!(
+            Role::Custom {
+                ref kind,
+                ref mut target
+            },
+            role.get_mut(name).unwrap()
+        );
+        if behavior::get(kind).and_then(|behavior| behavior.night_action()) != Some(Kind::Act) {
+            return Err(Error::Disallowed);
+        }
+        check_pending(name, waiting, ready, config)?;
+        if !survivors.contains(name) {
+            return Err(Error::SurvivorsOnly);
+        }
+        if !survivors.contains(&self.target) || name == &self.target {
+            return Err(Error::InvalidTarget(self.target));
+        }
+        *target = Some(self.target);
+        waiting.remove(name);
+        Ok(())
+    }
+}
+
+/// 最初の夜に二人を恋人として結びつける
+///
+/// 恋人の一方が死亡すると、もう一方も後を追って死亡する。
+/// 最後の二人が陣営の異なる恋人であれば、恋人たちが勝利する。
+/// 初日の夜を飛ばす場合は、最初の昼の次の夜に結びつける。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Bind {
+    /// 恋人になる二人
+    pub lovers: (Name, Name),
+}
+
+impl Request<'_> for Bind {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
+        assert_state!(
+            State::Night {
+                count,
+                role,
+                waiting,
+                ready,
+                survivors,
+                ..
+            },
+            state
+        );
+        assert_role!(
+            Role::Cupid {
+                ref mut binding,
+                ..
+            },
+            role.get_mut(name).unwrap()
+        );
+        if *count != config.first_night.count() {
+            return Err(Error::Disallowed);
+        }
+        check_pending(name, waiting, ready, config)?;
+        if !survivors.contains(name) {
+            return Err(Error::SurvivorsOnly);
+        }
+        let (first, second) = &self.lovers;
+        for lover in [first, second] {
+            if !survivors.contains(lover) {
+                return Err(Error::InvalidTarget(lover.to_owned()));
+            }
+        }
+        if first == second {
+            return Err(Error::InvalidTarget(second.to_owned()));
+        }
+        *binding = Some(self.lovers);
+        waiting.remove(name);
+        Ok(())
+    }
+}
+
+/// 魔女が、人狼の襲撃先を知った上で薬を使う
+///
+/// 薬の残っている魔女がいれば、全員の夜の行動が揃った後に魔女の行動する段階となる。
+/// 薬はそれぞれ一度しか使えない。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Potion {
+    /// 襲撃された人を治療の薬で助けるかどうか
+    pub heal: bool,
+    /// 毒の薬で殺害する人
+    pub poison: Option<Name>,
+}
+
+impl Request<'_> for Potion {
+    fn modify(self, name: &Name, state: &mut State, config: &Config) -> Result<(), Error> {
+        assert_state!(
+            State::Night {
+                count,
+                role,
+                waiting,
+                ready,
+                survivors,
+                stage,
+                ..
+            },
+            state
+        );
+        // 魔女以外には、魔女の行動する段階かどうかを知らせない
+        assert_role!(
+            Role::Witch {
+                heal_used,
+                poison_used,
+                healing,
+                poisoning
+            },
+            role.get_mut(name).unwrap()
+        );
+        let NightStage::Witch { attack } = stage else {
+            return Err(Error::InvalidState {
+                found: Phase::Night(*count),
+                expected: "Night (witch stage)".to_owned(),
+            });
+        };
+        check_pending(name, waiting, ready, config)?;
+        if !survivors.contains(name) {
+            return Err(Error::SurvivorsOnly);
+        }
+        if self.heal && (*heal_used || attack.is_none()) {
+            return Err(Error::Disallowed);
+        }
+        if let Some(target) = &self.poison {
+            if *poison_used {
+                return Err(Error::Disallowed);
+            }
+            if !survivors.contains(target) || name == target {
+                return Err(Error::InvalidTarget(target.to_owned()));
             }
         }
+        *healing = self.heal;
+        *poisoning = self.poison;
         waiting.remove(name);
         Ok(())
     }
@@ -0,0 +1,529 @@
+use super::{Error, Role, Team};
+use crate::{
+    master::Config,
+    request::Kind,
+    state::{Cause, Death, Name},
+};
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{PoisonError, RwLock},
+};
+
+/// 役職の振る舞い
+///
+/// 組み込みの役職もこのトレイトで実装されている。
+/// 独自の役職は`register`で登録し、`Config::custom`で人数を指定すると`Role::Custom`として配役される。
+pub trait RoleBehavior: Send + Sync {
+    /// 役職の名前。独自の役職では登録に用いる
+    fn name(&self) -> &str;
+
+    /// 勝利を共にする陣営
+    fn team(&self) -> Team;
+
+    /// 占いや霊媒で判定される陣営
+    fn divined_as(&self) -> Team {
+        Team::Citizen
+    }
+
+    /// 勝敗判定の際に人狼として数えられるかどうか
+    fn is_wolf(&self) -> bool {
+        false
+    }
+
+    /// 配役する人数
+    fn count(&self, config: &Config) -> usize {
+        config.custom.get(self.name()).copied().unwrap_or(0)
+    }
+
+    /// 夜に対象を選んで行うリクエストの種類。Noneであれば夜に行動しない。
+    /// 独自の役職では`Kind::Act`のみが使え、それ以外は`register`で拒否される
+    fn night_action(&self) -> Option<Kind> {
+        None
+    }
+
+    /// 夜の行動をスキップできるかどうか
+    fn skippable(&self, _config: &Config) -> bool {
+        true
+    }
+
+    /// 夜の解決の優先度。小さいものから順に解決する
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// 夜の行動を解決する。対象は夜に選んだ人
+    fn resolve_night(&self, _name: &Name, _target: Option<&Name>, _night: &mut Night<'_>) {}
+
+    /// 人狼の襲撃で死亡するかどうか
+    fn killable(&self) -> bool {
+        true
+    }
+
+    /// 占われると死亡するかどうか
+    fn cursed(&self) -> bool {
+        false
+    }
+
+    /// 他のプレイヤーの役職を見られるかどうか
+    fn sees(&self, _other: &Role) -> bool {
+        false
+    }
+
+    /// この死因で死亡すると、ゲームを終わらせて単独で勝利するかどうか
+    fn wins_by(&self, _cause: &Cause) -> bool {
+        false
+    }
+
+    /// 死亡した時に道連れにする候補。候補からランダムに一人が死亡する
+    fn drags(
+        &self,
+        _cause: &Cause,
+        _role: &HashMap<Name, Role>,
+        _survivors: &HashSet<Name>,
+    ) -> Vec<Name> {
+        Vec::new()
+    }
+}
+
+/// 夜の解決の途中経過
+///
+/// 全員の行動を解決した後に、人狼の襲撃先と呪殺が決定される。
+pub struct Night<'a> {
+    /// 役職
+    pub role: &'a HashMap<Name, Role>,
+    /// 生存している人
+    pub survivors: &'a HashSet<Name>,
+    /// 襲撃から守られている人
+    pub guarded: HashSet<Name>,
+    /// 人狼たちの襲撃先の提案
+    pub proposals: Vec<Proposal>,
+    /// 占われた人
+    pub divined: Vec<Name>,
+    /// 結びつけられた恋人たち
+    pub lovers: Vec<(Name, Name)>,
+    /// 人狼の襲撃先。魔女が行動する段階でのみ決定している
+    pub attack: Option<Name>,
+    /// 死亡した人
+    pub deaths: Vec<Death>,
+}
+
+impl<'a> Night<'a> {
+    pub(crate) fn new(role: &'a HashMap<Name, Role>, survivors: &'a HashSet<Name>) -> Self {
+        Self {
+            role,
+            survivors,
+            guarded: HashSet::new(),
+            proposals: Vec::new(),
+            divined: Vec::new(),
+            lovers: Vec::new(),
+            attack: None,
+            deaths: Vec::new(),
+        }
+    }
+}
+
+/// 人狼の襲撃先の提案
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proposal {
+    /// 提案した人狼
+    pub wolf: Name,
+    /// 襲撃先。Noneは襲撃しないことの提案
+    pub target: Option<Name>,
+    /// 提案した順番。後から提案したものほど大きい
+    pub sequence: usize,
+}
+
+/// 登録された独自の役職
+static REGISTRY: RwLock<BTreeMap<String, &'static dyn RoleBehavior>> = RwLock::new(BTreeMap::new());
+
+/// 独自の役職を登録する。同じ名前の役職が登録済みであれば置きかえる。
+/// 夜の行動が`Kind::Act`以外の役職は登録できない。
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+/// use werewolf::{
+///     master::{config::*, Master},
+///     request::{self, Kind},
+///     role::{behavior::*, Role, Team},
+///     state::{Name, State},
+/// };
+/// /// 自分以外を守る騎士
+/// struct Knight;
+/// impl RoleBehavior for Knight {
+///     fn name(&self) -> &str {
+///         "knight"
+///     }
+///     fn team(&self) -> Team {
+///         Team::Citizen
+///     }
+///     fn night_action(&self) -> Option<Kind> {
+///         Some(Kind::Act)
+///     }
+///     fn resolve_night(&self, _: &Name, target: Option<&Name>, night: &mut Night<'_>) {
+///         night.guarded.extend(target.cloned());
+///     }
+/// }
+/// register(Knight).unwrap();
+///
+/// let mut master = Master::from(Config {
+///     citizen: CitizenConfig { count: 1 },
+///     wolf: WolfConfig { count: 1, ..Default::default() },
+///     custom: BTreeMap::from([("knight".to_string(), 1)]),
+///     ..Default::default()
+/// });
+/// let tokens: Vec<_> = ["たろう", "はなこ", "めじろ"]
+///     .iter()
+///     .map(|name| master.signup(name.to_string()).unwrap())
+///     .collect();
+/// master.start().unwrap();
+/// let role_of = |token| {
+///     let viewer = master.viewer(token).unwrap();
+///     let State::Night { role, .. } = viewer.view_state() else { unreachable!() };
+///     role[viewer.name()].clone()
+/// };
+/// let find = |f: fn(&Role) -> bool| tokens.iter().find(|token| f(&role_of(token))).unwrap();
+/// let knight = find(|role| matches!(role, Role::Custom { kind, .. } if kind == "knight"));
+/// let wolf = find(Role::is_wolf);
+/// let citizen = find(|role| *role == Role::Citizen);
+/// let actions = master.viewer(knight).unwrap().available_actions();
+/// assert!(actions.iter().any(|action| action.kind == Kind::Act && action.targets.len() == 2));
+///
+/// // 騎士が守った市民は、人狼に襲撃されても死なない
+/// let target = master.get_name(citizen).unwrap().clone();
+/// let (knight, wolf, citizen) = (*knight, *wolf, *citizen);
+/// master.login(&knight).unwrap().execute(request::Act { target: target.clone() }).unwrap();
+/// master.login(&wolf).unwrap().execute(request::Kill { target }).unwrap();
+/// master.login(&citizen).unwrap().execute(request::Skip()).unwrap();
+/// let State::Day { deaths, survivors, .. } = master.viewer(&citizen).unwrap().view_state() else {
+///     unreachable!()
+/// };
+/// assert!(deaths.is_empty());
+/// assert_eq!(survivors.len(), 3);
+///
+/// /// 組み込みの役職のリクエストは使えない
+/// struct Oracle;
+/// impl RoleBehavior for Oracle {
+///     fn name(&self) -> &str {
+///         "oracle"
+///     }
+///     fn team(&self) -> Team {
+///         Team::Citizen
+///     }
+///     fn night_action(&self) -> Option<Kind> {
+///         Some(Kind::Divine)
+///     }
+/// }
+/// assert!(register(Oracle).is_err());
+/// assert!(get("oracle").is_none());
+/// ```
+pub fn register(behavior: impl RoleBehavior + 'static) -> Result<(), Error> {
+    // 組み込みの役職のリクエストは、対応する役職でなければ受け付けられない
+    if let Some(kind) = behavior.night_action() {
+        if kind != Kind::Act {
+            return Err(Error::UnsupportedAction(behavior.name().to_owned()));
+        }
+    }
+    // 登録はプロセスの間ずっと有効なので、解放しない
+    let behavior: &'static dyn RoleBehavior = Box::leak(Box::new(behavior));
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    registry.insert(behavior.name().to_owned(), behavior);
+    Ok(())
+}
+
+/// 登録された独自の役職を名前から探す。
+pub fn get(name: &str) -> Option<&'static dyn RoleBehavior> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry.get(name).copied()
+}
+
+/// 市民
+pub struct Citizen;
+
+impl RoleBehavior for Citizen {
+    fn name(&self) -> &str {
+        "citizen"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.citizen.count
+    }
+}
+
+/// 人狼
+pub struct Wolf;
+
+impl RoleBehavior for Wolf {
+    fn name(&self) -> &str {
+        "wolf"
+    }
+    fn team(&self) -> Team {
+        Team::Wolf
+    }
+    fn divined_as(&self) -> Team {
+        Team::Wolf
+    }
+    fn is_wolf(&self) -> bool {
+        true
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.wolf.count
+    }
+    fn night_action(&self) -> Option<Kind> {
+        Some(Kind::Kill)
+    }
+    fn skippable(&self, config: &Config) -> bool {
+        config.wolf.skippable
+    }
+    fn priority(&self) -> i32 {
+        1
+    }
+    fn resolve_night(&self, name: &Name, target: Option<&Name>, night: &mut Night<'_>) {
+        let sequence = match night.role.get(name) {
+            Some(Role::Wolf { sequence, .. }) => *sequence,
+            _ => 0,
+        };
+        night.proposals.push(Proposal {
+            wolf: name.to_owned(),
+            target: target.cloned(),
+            sequence,
+        });
+    }
+    fn sees(&self, other: &Role) -> bool {
+        other.is_wolf()
+    }
+}
+
+/// 占い師
+pub struct Seer;
+
+impl RoleBehavior for Seer {
+    fn name(&self) -> &str {
+        "seer"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.seer.count
+    }
+    fn night_action(&self) -> Option<Kind> {
+        Some(Kind::Divine)
+    }
+    fn skippable(&self, config: &Config) -> bool {
+        config.seer.skippable
+    }
+    fn priority(&self) -> i32 {
+        2
+    }
+    fn resolve_night(&self, _: &Name, target: Option<&Name>, night: &mut Night<'_>) {
+        night.divined.extend(target.cloned());
+    }
+}
+
+/// 狩人
+pub struct Hunter;
+
+impl RoleBehavior for Hunter {
+    fn name(&self) -> &str {
+        "hunter"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.hunter.count
+    }
+    fn night_action(&self) -> Option<Kind> {
+        Some(Kind::Guard)
+    }
+    fn skippable(&self, config: &Config) -> bool {
+        config.hunter.skippable
+    }
+    fn resolve_night(&self, _: &Name, target: Option<&Name>, night: &mut Night<'_>) {
+        night.guarded.extend(target.cloned());
+    }
+}
+
+/// 霊媒師
+pub struct Medium;
+
+impl RoleBehavior for Medium {
+    fn name(&self) -> &str {
+        "medium"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.medium.count
+    }
+}
+
+/// 狂人
+pub struct Madman;
+
+impl RoleBehavior for Madman {
+    fn name(&self) -> &str {
+        "madman"
+    }
+    fn team(&self) -> Team {
+        Team::Wolf
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.madman.count
+    }
+}
+
+/// 妖狐
+pub struct Fox;
+
+impl RoleBehavior for Fox {
+    fn name(&self) -> &str {
+        "fox"
+    }
+    fn team(&self) -> Team {
+        Team::Fox
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.fox.count
+    }
+    fn killable(&self) -> bool {
+        false
+    }
+    fn cursed(&self) -> bool {
+        true
+    }
+}
+
+/// キューピッド
+pub struct Cupid;
+
+impl RoleBehavior for Cupid {
+    fn name(&self) -> &str {
+        "cupid"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.cupid.count
+    }
+    fn night_action(&self) -> Option<Kind> {
+        Some(Kind::Bind)
+    }
+    fn resolve_night(&self, name: &Name, _: Option<&Name>, night: &mut Night<'_>) {
+        if let Some(Role::Cupid {
+            binding: Some(lovers),
+            ..
+        }) = night.role.get(name)
+        {
+            night.lovers.push(lovers.clone());
+        }
+    }
+}
+
+/// 魔女
+pub struct Witch;
+
+impl RoleBehavior for Witch {
+    fn name(&self) -> &str {
+        "witch"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.witch.count
+    }
+    fn resolve_night(&self, name: &Name, _: Option<&Name>, night: &mut Night<'_>) {
+        let Some(Role::Witch {
+            healing, poisoning, ..
+        }) = night.role.get(name)
+        else {
+            return;
+        };
+        // 治療は襲撃からの防護として扱う
+        if *healing {
+            night.guarded.extend(night.attack.clone());
+        }
+        if let Some(target) = poisoning {
+            if night.deaths.iter().all(|death| &death.name != target) {
+                night.deaths.push(Death {
+                    name: target.to_owned(),
+                    cause: Cause::Poison,
+                });
+            }
+        }
+    }
+}
+
+/// 共有者
+pub struct Mason;
+
+impl RoleBehavior for Mason {
+    fn name(&self) -> &str {
+        "mason"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.mason.count
+    }
+    fn sees(&self, other: &Role) -> bool {
+        matches!(other, Role::Mason)
+    }
+}
+
+/// 猫又
+pub struct Nekomata;
+
+impl RoleBehavior for Nekomata {
+    fn name(&self) -> &str {
+        "nekomata"
+    }
+    fn team(&self) -> Team {
+        Team::Citizen
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.nekomata.count
+    }
+    fn drags(
+        &self,
+        cause: &Cause,
+        role: &HashMap<Name, Role>,
+        survivors: &HashSet<Name>,
+    ) -> Vec<Name> {
+        match cause {
+            // 襲撃されれば人狼を、追放されれば生存者を道連れにする
+            Cause::Attack => survivors
+                .iter()
+                .filter(|name| role[*name].is_wolf())
+                .cloned()
+                .collect(),
+            Cause::Exile => survivors.iter().cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// 道化師
+pub struct Jester;
+
+impl RoleBehavior for Jester {
+    fn name(&self) -> &str {
+        "jester"
+    }
+    fn team(&self) -> Team {
+        Team::Jester
+    }
+    fn count(&self, config: &Config) -> usize {
+        config.jester.count
+    }
+    fn wins_by(&self, cause: &Cause) -> bool {
+        matches!(cause, Cause::Exile)
+    }
+}
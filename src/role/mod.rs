@@ -0,0 +1,237 @@
+pub mod behavior;
+pub use behavior::RoleBehavior;
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use strum::{EnumDiscriminants, EnumIter, IntoStaticStr};
+use thiserror::Error;
+
+use crate::state::Name;
+
+/// 認証周辺のエラー
+#[derive(Error, Debug, Serialize, IntoStaticStr)]
+#[serde(tag = "code", content = "detail", rename_all = "camelCase")]
+#[strum(serialize_all = "camelCase")]
+pub enum Error {
+    /// 指定された名前のロールが存在していない場合。
+    #[error("Cannot find role named {0}.")]
+    UnknownRole(String),
+    /// 独自の役職の夜の行動が`Kind::Act`でない場合。
+    #[error("Custom role {0} can only act at night with `act`.")]
+    UnsupportedAction(String),
+}
+
+impl Error {
+    /// エラーコードを返す。
+    pub fn code(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// 役職
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, EnumIter, EnumDiscriminants)]
+#[strum_discriminants(
+    name(RoleKind),
+    derive(Serialize, Deserialize, Hash),
+    serde(rename_all = "camelCase")
+)]
+pub enum Role {
+    /// 市民
+    Citizen,
+    /// 人狼
+    /// `sequence`は襲撃先を提案した順番で、後から提案したものほど大きい
+    Wolf {
+        killing: Option<Name>,
+        sequence: usize,
+    },
+    /// 占い師
+    /// HashMapの値は占いで判定されたチーム
+    Seer {
+        prediction: HashMap<Name, Team>,
+        divining: Option<Name>,
+    },
+    /// 狩人
+    Hunter {
+        guarding: Option<Name>,
+        guarded: Option<Name>,
+    },
+    /// 霊媒師
+    /// 前日の昼に追放された人の陣営を夜に知る
+    Medium { revelation: HashMap<Name, Team> },
+    /// 狂人
+    /// 人狼陣営だが、占いや霊媒では市民と判定される
+    Madman,
+    /// 妖狐
+    /// 人狼の襲撃では死なないが、占われると死ぬ
+    Fox,
+    /// キューピッド
+    /// 最初の夜に二人を恋人として結びつける。
+    /// `binding`は今夜選んだ二人で、夜が明けると`bound`に移る
+    Cupid {
+        binding: Option<(Name, Name)>,
+        bound: Option<(Name, Name)>,
+    },
+    /// 魔女
+    /// 人狼の襲撃先を知った上で、一度ずつ治療の薬と毒の薬を使える
+    Witch {
+        heal_used: bool,
+        poison_used: bool,
+        healing: bool,
+        poisoning: Option<Name>,
+    },
+    /// 共有者
+    /// 市民だが、共有者同士は互いの役職を知っている
+    Mason,
+    /// 猫又
+    /// 人狼に襲撃されると人狼を、追放されると生存者を一人道連れにする
+    Nekomata,
+    /// 道化師
+    /// 昼の投票で追放されると単独で勝利する
+    Jester,
+    /// 独自の役職
+    /// 振る舞いは`behavior::register`で登録されたものに従う
+    Custom { kind: String, target: Option<Name> },
+}
+
+impl Role {
+    /// 役職の振る舞いを返す。
+    /// 登録されていない独自の役職は市民として振る舞う。
+    pub fn behavior(&self) -> &'static dyn RoleBehavior {
+        match self {
+            Self::Citizen => &behavior::Citizen,
+            Self::Wolf { .. } => &behavior::Wolf,
+            Self::Seer { .. } => &behavior::Seer,
+            Self::Hunter { .. } => &behavior::Hunter,
+            Self::Medium { .. } => &behavior::Medium,
+            Self::Madman => &behavior::Madman,
+            Self::Fox => &behavior::Fox,
+            Self::Cupid { .. } => &behavior::Cupid,
+            Self::Witch { .. } => &behavior::Witch,
+            Self::Mason => &behavior::Mason,
+            Self::Nekomata => &behavior::Nekomata,
+            Self::Jester => &behavior::Jester,
+            Self::Custom { kind, .. } => behavior::get(kind).unwrap_or(&behavior::Citizen),
+        }
+    }
+
+    /// 勝利を共にするチームを返す。
+    pub fn team(&self) -> Team {
+        self.behavior().team()
+    }
+
+    /// 占いや霊媒で判定されるチームを返す。
+    pub fn divined_as(&self) -> Team {
+        self.behavior().divined_as()
+    }
+
+    /// 夜の行動の対象を返す。
+    pub fn target(&self) -> Option<&Name> {
+        match self {
+            Self::Wolf {
+                killing: target, ..
+            }
+            | Self::Hunter {
+                guarding: target, ..
+            }
+            | Self::Seer {
+                divining: target, ..
+            }
+            | Self::Custom { target, .. } => target.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// 夜の行動の対象を取り消す。
+    pub fn clear_target(&mut self) {
+        if let Self::Wolf {
+            killing: target, ..
+        }
+        | Self::Hunter {
+            guarding: target, ..
+        }
+        | Self::Seer {
+            divining: target, ..
+        }
+        | Self::Custom { target, .. } = self
+        {
+            *target = None;
+        }
+        if let Self::Cupid { binding, .. } = self {
+            *binding = None;
+        }
+        if let Self::Witch {
+            healing, poisoning, ..
+        } = self
+        {
+            *healing = false;
+            *poisoning = None;
+        }
+    }
+
+    /// 勝敗判定の際に人狼として数えられるかどうか。
+    pub fn is_wolf(&self) -> bool {
+        self.behavior().is_wolf()
+    }
+}
+
+/// 陣営
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum Team {
+    /// 市民陣営
+    Citizen,
+    /// 人狼陣営
+    Wolf,
+    /// 妖狐陣営
+    Fox,
+    /// 恋人たち
+    Lovers,
+    /// 道化師
+    Jester,
+}
+
+/// 勝者
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Winner {
+    /// 陣営の勝利
+    Team { team: Team },
+    /// 個人の単独勝利
+    Solo { name: Name },
+}
+
+/// 生存者と役職から勝利したチームを決定する。
+/// 決着がついていない場合はNoneを返す。
+///
+/// 人狼が全滅すれば市民陣営、人狼が生存者の半数以上になれば人狼陣営の勝利となる。
+/// ただし決着の時点で妖狐が生存していれば、妖狐陣営が勝利を奪う。
+/// # Example
+/// ```
+/// use std::collections::{HashMap, HashSet};
+/// use werewolf::role::{winner, Role, Team};
+/// let role = HashMap::from([
+///     ("たろう".to_string(), Role::Wolf { killing: None, sequence: 0 }),
+///     ("はなこ".to_string(), Role::Citizen),
+///     ("めじろ".to_string(), Role::Fox),
+/// ]);
+/// let survivors = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<HashSet<_>>();
+/// assert_eq!(winner(&survivors(&["たろう", "はなこ", "めじろ"]), &role), None);
+/// assert_eq!(winner(&survivors(&["たろう", "はなこ"]), &role), Some(Team::Wolf));
+/// assert_eq!(winner(&survivors(&["はなこ", "めじろ"]), &role), Some(Team::Fox));
+/// ```
+pub fn winner(survivors: &HashSet<Name>, role: &HashMap<Name, Role>) -> Option<Team> {
+    let roles = || survivors.iter().filter_map(|name| role.get(name));
+    let wolves = roles().filter(|role| role.is_wolf()).count();
+    let winner = if wolves == 0 {
+        Team::Citizen
+    } else if wolves * 2 >= survivors.len() {
+        Team::Wolf
+    } else {
+        return None;
+    };
+    if roles().any(|role| role.team() == Team::Fox) {
+        Some(Team::Fox)
+    } else {
+        Some(winner)
+    }
+}
@@ -1,17 +1,15 @@
 use crate::{
-    master::Config,
-    role::{Role, Team},
+    master::{config::TieBreak, Config},
+    role::{Role, Winner},
 };
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
 pub type Name = String;
 
 /// フェーズ
-// unsafeでCell内のクローンを行うためメンバに注意。
-// ArcやRcなど禁止。
-#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub enum State {
     /// メンバー募集中
     Waiting {
@@ -26,8 +24,24 @@ pub enum State {
         role: HashMap<Name, Role>,
         /// 待機中の人
         waiting: HashSet<Name>,
+        /// 行動を確定した人
+        ready: HashSet<Name>,
         /// 生存している人
         survivors: HashSet<Name>,
+
+        /// 恋人たち
+        lovers: Vec<(Name, Name)>,
+
+        /// 直前の昼に死亡した人
+        deaths: Vec<Death>,
+        /// 直前の昼の得票数
+        tally: HashMap<Name, usize>,
+        /// 直前の昼に、誰も追放しないことへ投票した人数
+        no_exile: usize,
+        /// 直前の昼の投票が同票だった場合に適用された処理
+        tie_break: Option<TieBreak>,
+        /// 夜の段階
+        stage: NightStage,
     },
     /// 昼
     Day {
@@ -37,23 +51,56 @@ pub enum State {
         role: HashMap<Name, Role>,
         /// 待機中の人
         waiting: HashSet<Name>,
+        /// 行動を確定した人
+        ready: HashSet<Name>,
         /// 生存している人
         survivors: HashSet<Name>,
 
-        /// 投票
-        votes: HashMap<Name, Name>,
+        /// 恋人たち
+        lovers: Vec<(Name, Name)>,
+
+        /// 投票。Noneは誰も追放しないことへの投票
+        votes: HashMap<Name, Option<Name>>,
         /// 追放の候補者
         candidates: HashSet<Name>,
+        /// 直前の夜に死亡した人。空であれば平和な朝
+        deaths: Vec<Death>,
+        /// 決選投票の回数
+        runoff: usize,
+    },
+    /// 死亡した狩人が道連れにする人を選んでいる
+    Revenge {
+        /// 狩人が死亡した場面の周回
+        count: usize,
+        /// 道連れにする人を選んでいる狩人
+        waiting: HashSet<Name>,
+        /// 狩人と、その道連れ先
+        shots: HashMap<Name, Name>,
+        /// 道連れの後に移行する場面
+        next: Box<State>,
     },
     /// 終了
     End {
         /// 役職
         role: HashMap<Name, Role>,
-        /// 勝利したチーム
-        winner: Team,
+        /// 勝者。陣営や個人が複数同時に勝利することもある
+        winners: Vec<Winner>,
     },
 }
 
+impl State {
+    /// 場面の種類と周回を返す。
+    pub fn phase(&self) -> Phase {
+        match self {
+            Self::Waiting { .. } => Phase::Waiting,
+            Self::Night { count, .. } => Phase::Night(*count),
+            Self::Day { count, .. } => Phase::Day(*count),
+            Self::Revenge { count, .. } => Phase::Revenge(*count),
+            Self::End { .. } => Phase::End,
+        }
+    }
+}
+
 impl Default for State {
     fn default() -> Self {
         Self::Waiting {
@@ -61,3 +108,66 @@ impl Default for State {
         }
     }
 }
+
+/// 死亡の記録
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Death {
+    /// 死亡した人
+    pub name: Name,
+    /// 死因
+    pub cause: Cause,
+}
+
+/// 死因
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Cause {
+    /// 昼の投票で追放された
+    Exile,
+    /// 人狼に襲撃された
+    Attack,
+    /// 占われて呪殺された
+    Curse,
+    /// 死亡した狩人に道連れにされた
+    Revenge,
+    /// 恋人の死を追って死亡した
+    Heartbreak,
+    /// 魔女に毒を盛られた
+    Poison,
+    /// 死亡した猫又に道連れにされた
+    Dragged,
+    /// 夜の間に死亡した。
+    /// 死因を知ることのできない人には、夜の死因がこれに置きかえられる
+    Night,
+}
+
+/// 夜の段階
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum NightStage {
+    /// 全員が並行して行動する
+    #[default]
+    Main,
+    /// 人狼の襲撃先が決定した後に、魔女が行動する。
+    /// 魔女以外には、この段階自体が伏せられる
+    Witch {
+        /// 人狼の襲撃先
+        attack: Option<Name>,
+    },
+}
+
+/// 場面の種類と周回
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Phase {
+    /// メンバー募集中
+    Waiting,
+    /// 夜
+    Night(usize),
+    /// 昼
+    Day(usize),
+    /// 死亡した狩人の道連れ。周回は狩人が死亡した場面のもの
+    Revenge(usize),
+    /// 終了
+    End,
+}
@@ -0,0 +1,188 @@
+//! 結合テストで共有する、ゲームを進行させる補助
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
+use strum::IntoEnumIterator;
+use werewolf::{
+    event::Event,
+    master::{clock::Clock, Config, Master, Token},
+    request::{self, Action, Any},
+    role::Role,
+    state::{Name, State},
+    Error,
+};
+
+/// 参加者の名前
+const NAMES: [&str; 12] = [
+    "たろう",
+    "はなこ",
+    "めじろ",
+    "ぽち",
+    "じろう",
+    "さくら",
+    "もも",
+    "ゆき",
+    "はると",
+    "あおい",
+    "そら",
+    "りく",
+];
+
+/// 指定の時間だけ進んだ時計
+struct Later(u64);
+
+impl Clock for Later {
+    fn now(&self) -> SystemTime {
+        SystemTime::now() + Duration::from_secs(3600 * self.0)
+    }
+}
+
+/// 役職の割り当てに依存せずにゲームを進めるための、マスターと参加者の組
+pub struct Game {
+    pub master: Master,
+    tokens: HashMap<Name, Token>,
+    /// 開始時の役職
+    pub role: HashMap<Name, Role>,
+    /// 時計を進めた時間
+    hours: u64,
+}
+
+impl Game {
+    /// 配役の人数だけ参加者を登録して、ゲームを開始する。
+    pub fn start(config: Config) -> Self {
+        let count = Role::iter()
+            .filter(|role| !matches!(role, Role::Custom { .. }))
+            .map(|role| role.behavior().count(&config))
+            .chain(config.custom.values().copied())
+            .sum();
+        let mut master = Master::from(config);
+        let tokens = NAMES[..count]
+            .iter()
+            .map(|name| (name.to_string(), master.signup(name.to_string()).unwrap()))
+            .collect();
+        master.start().unwrap();
+        let mut game = Self {
+            master,
+            tokens,
+            role: HashMap::new(),
+            hours: 0,
+        };
+        game.role = match game.latest() {
+            State::Day { role, .. } | State::Night { role, .. } => role,
+            state => unreachable!("{state:?}"),
+        };
+        game
+    }
+
+    /// 条件を満たす役職の人のうち、名前順で最初の人を返す。
+    pub fn find(&self, f: impl Fn(&Role) -> bool) -> Name {
+        self.all(f).into_iter().next().unwrap()
+    }
+
+    /// 条件を満たす役職の人を名前順に返す。
+    pub fn all(&self, f: impl Fn(&Role) -> bool) -> Vec<Name> {
+        let mut names: Vec<_> = self
+            .role
+            .iter()
+            .filter(|(_, role)| f(role))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// リクエストを実行する。
+    pub fn execute(&mut self, name: &Name, req: impl Into<Any>) -> Result<(), Error> {
+        let req: Any = req.into();
+        self.master.login(&self.tokens[name]).unwrap().execute(req)
+    }
+
+    /// リクエストを実行せずに、実行した場合に起きるエラーを返す。
+    pub fn validate(&self, name: &Name, req: impl Into<Any>) -> Result<(), request::Error> {
+        let req: Any = req.into();
+        self.master
+            .viewer(&self.tokens[name])
+            .unwrap()
+            .validate(&req)
+    }
+
+    /// その人が閲覧できる状態を返す。
+    pub fn view(&self, name: &Name) -> State {
+        self.master.viewer(&self.tokens[name]).unwrap().view_state()
+    }
+
+    /// その人が閲覧できる出来事の記録を返す。
+    pub fn history(&self, name: &Name) -> Vec<Event> {
+        self.master.viewer(&self.tokens[name]).unwrap().history()
+    }
+
+    /// その人が現在実行可能なリクエストを返す。
+    pub fn actions(&self, name: &Name) -> Vec<Action> {
+        self.master
+            .viewer(&self.tokens[name])
+            .unwrap()
+            .available_actions()
+    }
+
+    /// 時計を一時間進めて、締め切りを過ぎていれば場面を進める。
+    pub fn expire(&mut self) -> bool {
+        self.hours += 1;
+        let master = std::mem::take(&mut self.master);
+        self.master = master.with_clock(Later(self.hours));
+        self.master.tick()
+    }
+
+    /// 直前の場面の移行で記録された、伏せられていない状態を返す。
+    pub fn latest(&self) -> State {
+        self.master
+            .log()
+            .iter()
+            .rev()
+            .find_map(|event| match event {
+                Event::Transition { to, .. } => Some(to.clone()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// 現在の生存者を名前順に返す。
+    pub fn survivors(&self) -> Vec<Name> {
+        let state = match self.latest() {
+            State::Revenge { next, .. } => *next,
+            state => state,
+        };
+        let (State::Day { survivors, .. } | State::Night { survivors, .. }) = state else {
+            return Vec::new();
+        };
+        let mut survivors: Vec<_> = survivors.into_iter().collect();
+        survivors.sort();
+        survivors
+    }
+
+    /// 生存者が夜に行動する。指定されていない人は行動をスキップする。
+    pub fn night(&mut self, actions: &[(&Name, Any)]) {
+        for name in self.survivors() {
+            let req = actions
+                .iter()
+                .find(|(actor, _)| **actor == name)
+                .map_or_else(|| request::Skip().into(), |(_, req)| req.clone());
+            self.execute(&name, req).unwrap();
+        }
+    }
+
+    /// 生存者全員が一人に投票する。投票される人は、名前順で最初の他の生存者に投票する。
+    pub fn vote(&mut self, target: &Name) {
+        let survivors = self.survivors();
+        let other = survivors.iter().find(|name| *name != target).unwrap();
+        for name in survivors.iter() {
+            let target = if name == target { other } else { target };
+            let vote = request::Vote {
+                target: Some(target.clone()),
+            };
+            self.execute(name, vote).unwrap();
+        }
+    }
+}
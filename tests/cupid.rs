@@ -0,0 +1,140 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    request,
+    role::{Role, Team, Winner},
+    state::{Cause, Death, State},
+};
+
+fn config(first_night: FirstNightConfig) -> Config {
+    Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        cupid: CupidConfig { count: 1 },
+        first_night,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn lover_dies_of_heartbreak() {
+    let mut game = Game::start(config(Default::default()));
+    let cupid = game.find(|role| matches!(role, Role::Cupid { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+
+    // 二人の市民を恋人にし、その一方を襲撃する
+    let bind = request::Bind {
+        lovers: (c[0].clone(), c[1].clone()),
+    };
+    let kill = request::Kill {
+        target: c[0].clone(),
+    };
+    game.night(&[(&cupid, bind.into()), (&wolf, kill.into())]);
+    let State::Day { deaths, lovers, .. } = game.latest() else {
+        unreachable!()
+    };
+    assert_eq!(lovers, vec![(c[0].clone(), c[1].clone())]);
+    assert_eq!(
+        deaths,
+        vec![
+            Death {
+                name: c[0].clone(),
+                cause: Cause::Attack
+            },
+            Death {
+                name: c[1].clone(),
+                cause: Cause::Heartbreak
+            },
+        ]
+    );
+}
+
+#[test]
+fn lovers_of_different_teams_win_as_the_last_two() {
+    let mut game = Game::start(config(FirstNightConfig {
+        skip: true,
+        ..Default::default()
+    }));
+    let cupid = game.find(|role| matches!(role, Role::Cupid { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+
+    // 初日の夜を飛ばしたので、2日目の夜に人狼と市民を恋人にし、残りの市民を襲撃する
+    game.vote(&c[2]);
+    let bind = request::Bind {
+        lovers: (wolf.clone(), c[0].clone()),
+    };
+    let kill = request::Kill {
+        target: c[1].clone(),
+    };
+    game.night(&[(&cupid, bind.into()), (&wolf, kill.into())]);
+
+    // キューピッドを追放すると、恋人たちだけが残る
+    game.vote(&cupid);
+    let State::End { winners, .. } = game.view(&cupid) else {
+        unreachable!()
+    };
+    assert_eq!(winners, vec![Winner::Team { team: Team::Lovers }]);
+}
+
+#[test]
+fn cupid_keeps_seeing_the_lovers_after_the_binding_night() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 6 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        cupid: CupidConfig { count: 1 },
+        ..Default::default()
+    });
+    let cupid = game.find(|role| matches!(role, Role::Cupid { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+    let pair = (c[0].clone(), c[1].clone());
+    let lovers = |game: &Game| match game.view(&cupid) {
+        State::Day { lovers, .. } | State::Night { lovers, .. } => lovers,
+        state => unreachable!("{state:?}"),
+    };
+
+    let bind = request::Bind {
+        lovers: pair.clone(),
+    };
+    let kill = request::Kill {
+        target: c[2].clone(),
+    };
+    game.night(&[(&cupid, bind.into()), (&wolf, kill.into())]);
+    assert_eq!(lovers(&game), vec![pair.clone()]);
+
+    // 2日目の夜は結びつけられないのでスキップする
+    game.vote(&c[3]);
+    let bind = request::Bind {
+        lovers: (c[4].clone(), c[5].clone()),
+    };
+    assert!(matches!(
+        game.validate(&cupid, bind),
+        Err(request::Error::Disallowed)
+    ));
+    let kill = request::Kill {
+        target: c[4].clone(),
+    };
+    game.night(&[(&wolf, kill.into())]);
+    let State::Day { count, role, .. } = game.view(&cupid) else {
+        unreachable!()
+    };
+    assert_eq!(count, 3);
+    assert_eq!(
+        role[&cupid],
+        Role::Cupid {
+            binding: None,
+            bound: Some(pair.clone()),
+        }
+    );
+    assert_eq!(lovers(&game), vec![pair]);
+}
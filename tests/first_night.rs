@@ -0,0 +1,129 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    request::{self, Kind},
+    role::{Role, Team},
+    state::State,
+};
+
+fn config(first_night: FirstNightConfig) -> Config {
+    Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        first_night,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn no_attack_keeps_wolves_hidden() {
+    let mut game = Game::start(config(FirstNightConfig {
+        no_attack: true,
+        ..Default::default()
+    }));
+    let wolf = game.find(Role::is_wolf);
+    let citizens = game.all(|role| *role == Role::Citizen);
+
+    // 人狼も行動を待たれるので、待機中の人から人狼が分からない
+    let State::Night { waiting, .. } = game.view(&citizens[0]) else {
+        unreachable!()
+    };
+    assert_eq!(waiting, game.survivors().into_iter().collect());
+
+    // 人狼は襲撃できず、スキップできない設定でもスキップする
+    let kill = request::Kill {
+        target: citizens[0].clone(),
+    };
+    assert!(matches!(
+        game.validate(&wolf, kill),
+        Err(request::Error::Disallowed)
+    ));
+    let kinds: Vec<_> = game
+        .actions(&wolf)
+        .iter()
+        .map(|action| action.kind)
+        .collect();
+    assert_eq!(kinds, vec![Kind::Skip]);
+    game.night(&[]);
+    let State::Day { deaths, .. } = game.view(&wolf) else {
+        unreachable!()
+    };
+    assert!(deaths.is_empty());
+
+    // 次の夜からは襲撃できる
+    game.vote(&citizens[0]);
+    let kill = request::Kill {
+        target: citizens[1].clone(),
+    };
+    assert!(game.validate(&wolf, kill).is_ok());
+}
+
+#[test]
+fn no_attack_applies_to_the_first_night_after_skip() {
+    let mut game = Game::start(config(FirstNightConfig {
+        no_attack: true,
+        skip: true,
+        ..Default::default()
+    }));
+    let wolf = game.find(Role::is_wolf);
+    let citizens = game.all(|role| *role == Role::Citizen);
+
+    game.vote(&citizens[0]);
+    let kill = request::Kill {
+        target: citizens[1].clone(),
+    };
+    assert!(matches!(
+        game.validate(&wolf, kill),
+        Err(request::Error::Disallowed)
+    ));
+    game.night(&[]);
+    let State::Day { count, deaths, .. } = game.view(&wolf) else {
+        unreachable!()
+    };
+    assert_eq!(count, 3);
+    assert!(deaths.is_empty());
+}
+
+#[test]
+fn random_white_tells_seers_a_non_wolf() {
+    let config = |random_white| Config {
+        citizen: CitizenConfig { count: 2 },
+        seer: SeerConfig {
+            count: 1,
+            ..Default::default()
+        },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        first_night: FirstNightConfig {
+            random_white,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let predictions = |game: &Game| {
+        let seer = game.find(|role| matches!(role, Role::Seer { .. }));
+        let Role::Seer { prediction, .. } = &game.role[&seer] else {
+            unreachable!()
+        };
+        prediction.clone()
+    };
+
+    let game = Game::start(config(true));
+    let seer = game.find(|role| matches!(role, Role::Seer { .. }));
+    let prediction = predictions(&game);
+    assert_eq!(prediction.len(), 1);
+    let (white, team) = prediction.into_iter().next().unwrap();
+    assert_eq!(team, Team::Citizen);
+    assert_ne!(white, seer);
+    assert!(!game.role[&white].is_wolf());
+
+    let game = Game::start(config(false));
+    assert!(predictions(&game).is_empty());
+}
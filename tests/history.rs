@@ -0,0 +1,129 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    event::Event,
+    master::config::*,
+    request::{self, Kind},
+    role::Role,
+    state::{Name, NightStage, State},
+};
+
+/// 記録に含まれるリクエストの、リクエスト元と種類
+fn requests(history: &[Event]) -> Vec<(Name, Kind)> {
+    history
+        .iter()
+        .filter_map(|event| match event {
+            Event::Request { name, request, .. } => Some((name.clone(), Kind::from(request))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 記録に魔女の行動する段階への移行が含まれるかどうか
+fn has_witch_stage(history: &[Event]) -> bool {
+    history.iter().any(|event| {
+        matches!(
+            event,
+            Event::Transition {
+                to: State::Night {
+                    stage: NightStage::Witch { .. },
+                    ..
+                },
+                ..
+            }
+        )
+    })
+}
+
+#[test]
+fn history_hides_night_actions_until_the_end() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 2,
+            ..Default::default()
+        },
+        seer: SeerConfig {
+            count: 1,
+            ..Default::default()
+        },
+        hunter: HunterConfig {
+            count: 1,
+            ..Default::default()
+        },
+        witch: WitchConfig { count: 1 },
+        ..Default::default()
+    });
+    let wolves = game.all(Role::is_wolf);
+    let seer = game.find(|role| matches!(role, Role::Seer { .. }));
+    let hunter = game.find(|role| matches!(role, Role::Hunter { .. }));
+    let witch = game.find(|role| matches!(role, Role::Witch { .. }));
+    let c = game.all(|role| *role == Role::Citizen);
+    let kill = |target: &String| {
+        request::Kill {
+            target: target.clone(),
+        }
+        .into()
+    };
+    let guard = |target: &String| {
+        request::Guard {
+            target: target.clone(),
+        }
+        .into()
+    };
+
+    let divine = request::Divine {
+        target: c[1].clone(),
+    };
+    game.night(&[
+        (&wolves[0], kill(&c[0])),
+        (&wolves[1], kill(&c[0])),
+        (&seer, divine.into()),
+        (&hunter, guard(&c[2])),
+    ]);
+    let poison = request::Potion {
+        heal: false,
+        poison: Some(c[1].clone()),
+    };
+    game.execute(&witch, poison).unwrap();
+    assert!(matches!(game.view(&c[2]), State::Day { count: 1, .. }));
+
+    // 市民には、他の人の夜の行動も魔女の行動する段階も見えない
+    let history = game.history(&c[2]);
+    let secret = [Kind::Kill, Kind::Divine, Kind::Guard, Kind::Potion];
+    assert!(requests(&history)
+        .iter()
+        .all(|(name, kind)| *name == c[2] || !secret.contains(kind)));
+    assert!(requests(&history).contains(&(c[2].clone(), Kind::Skip)));
+    assert!(!has_witch_stage(&history));
+
+    // 人狼には仲間の襲撃先が見えるが、他の役職の行動は見えない
+    let history = game.history(&wolves[0]);
+    assert!(requests(&history).contains(&(wolves[1].clone(), Kind::Kill)));
+    assert!(!requests(&history).contains(&(seer.clone(), Kind::Divine)));
+    assert!(!has_witch_stage(&history));
+
+    // 魔女には自分の行動と、魔女の行動する段階が見える
+    let history = game.history(&witch);
+    assert!(requests(&history).contains(&(witch.clone(), Kind::Potion)));
+    assert!(!requests(&history).contains(&(wolves[1].clone(), Kind::Kill)));
+    assert!(has_witch_stage(&history));
+
+    // 人狼を二人とも追放して、ゲームを終わらせる
+    game.vote(&wolves[0]);
+    let divine = request::Divine {
+        target: witch.clone(),
+    };
+    game.night(&[
+        (&wolves[1], kill(&c[2])),
+        (&seer, divine.into()),
+        (&hunter, guard(&seer)),
+    ]);
+    game.execute(&witch, request::Skip()).unwrap();
+    game.vote(&wolves[1]);
+    assert!(matches!(game.view(&c[2]), State::End { .. }));
+
+    // 終了後は、全ての記録が見える
+    assert_eq!(game.history(&c[2]), game.master.log());
+}
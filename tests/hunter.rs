@@ -0,0 +1,107 @@
+mod common;
+
+use common::Game;
+use std::time::Duration;
+use werewolf::{
+    master::config::*,
+    request,
+    role::{Role, Team, Winner},
+    state::{Phase, State},
+};
+
+fn config(first_night: FirstNightConfig, time_limit: TimeLimitConfig) -> Config {
+    Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        hunter: HunterConfig {
+            count: 1,
+            revenge: true,
+            ..Default::default()
+        },
+        first_night,
+        time_limit,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn exiled_hunter_shoots_a_wolf() {
+    let mut game = Game::start(config(
+        FirstNightConfig {
+            skip: true,
+            ..Default::default()
+        },
+        Default::default(),
+    ));
+    let hunter = game.find(|role| matches!(role, Role::Hunter { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let citizen = game.find(|role| *role == Role::Citizen);
+
+    game.vote(&hunter);
+    let State::Revenge { waiting, .. } = game.view(&citizen) else {
+        unreachable!()
+    };
+    assert_eq!(waiting, [hunter.clone()].into());
+
+    // 道連れにできるのは死亡した狩人のみで、対象は生存者に限る
+    let shoot = |target: &String| request::Shoot {
+        target: target.clone(),
+    };
+    assert!(matches!(
+        game.validate(&citizen, shoot(&wolf)),
+        Err(request::Error::InvalidRole { .. })
+    ));
+    assert!(matches!(
+        game.validate(&hunter, shoot(&hunter)),
+        Err(request::Error::InvalidTarget(_))
+    ));
+
+    // 人狼を道連れにすると、その場で市民陣営が勝利する
+    game.execute(&hunter, shoot(&wolf)).unwrap();
+    let State::End { winners, .. } = game.view(&citizen) else {
+        unreachable!()
+    };
+    assert_eq!(
+        winners,
+        vec![Winner::Team {
+            team: Team::Citizen
+        }]
+    );
+}
+
+#[test]
+fn attacked_hunter_shoots_nobody_after_the_deadline() {
+    let mut game = Game::start(config(
+        Default::default(),
+        TimeLimitConfig {
+            revenge: Some(Duration::from_secs(60)),
+            ..Default::default()
+        },
+    ));
+    let hunter = game.find(|role| matches!(role, Role::Hunter { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let citizen = game.find(|role| *role == Role::Citizen);
+
+    let kill = request::Kill {
+        target: hunter.clone(),
+    };
+    let guard = request::Guard {
+        target: citizen.clone(),
+    };
+    game.night(&[(&wolf, kill.into()), (&hunter, guard.into())]);
+    assert_eq!(game.view(&citizen).phase(), Phase::Revenge(0));
+
+    assert!(game.expire());
+    let State::Day {
+        deaths, survivors, ..
+    } = game.view(&citizen)
+    else {
+        unreachable!()
+    };
+    assert_eq!(deaths.len(), 1);
+    assert_eq!(deaths[0].name, hunter);
+    assert_eq!(survivors.len(), 4);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    role::{Role, Winner},
+    state::State,
+};
+
+#[test]
+fn exiled_jester_wins_alone() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 2 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        jester: JesterConfig { count: 1 },
+        first_night: FirstNightConfig {
+            skip: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let jester = game.find(|role| *role == Role::Jester);
+    let citizen = game.find(|role| *role == Role::Citizen);
+    game.vote(&jester);
+
+    let State::End { winners, .. } = game.view(&citizen) else {
+        unreachable!()
+    };
+    assert_eq!(winners, vec![Winner::Solo { name: jester }]);
+}
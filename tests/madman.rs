@@ -0,0 +1,95 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    request,
+    role::{Role, Team, Winner},
+    state::State,
+};
+
+#[test]
+fn madman_is_divined_and_revealed_as_a_citizen() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        seer: SeerConfig {
+            count: 1,
+            ..Default::default()
+        },
+        medium: MediumConfig { count: 1 },
+        madman: MadmanConfig { count: 1 },
+        ..Default::default()
+    });
+    let seer = game.find(|role| matches!(role, Role::Seer { .. }));
+    let medium = game.find(|role| matches!(role, Role::Medium { .. }));
+    let madman = game.find(|role| *role == Role::Madman);
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+
+    let divine = request::Divine {
+        target: madman.clone(),
+    };
+    let kill = request::Kill {
+        target: c[0].clone(),
+    };
+    game.night(&[(&seer, divine.into()), (&wolf, kill.into())]);
+    let State::Day { role, .. } = game.view(&seer) else {
+        unreachable!()
+    };
+    let Role::Seer { prediction, .. } = &role[&seer] else {
+        unreachable!()
+    };
+    assert_eq!(prediction[&madman], Team::Citizen);
+
+    game.vote(&madman);
+    let State::Night { role, .. } = game.view(&medium) else {
+        unreachable!()
+    };
+    assert_eq!(
+        role[&medium],
+        Role::Medium {
+            revelation: [(madman.clone(), Team::Citizen)].into()
+        }
+    );
+}
+
+#[test]
+fn madman_wins_with_wolves_without_counting_as_one() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        madman: MadmanConfig { count: 1 },
+        first_night: FirstNightConfig {
+            skip: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let madman = game.find(|role| *role == Role::Madman);
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+
+    // 人狼と狂人が生存者の半数になっても、狂人は人狼として数えない
+    game.vote(&c[0]);
+    assert!(matches!(game.latest(), State::Night { count: 2, .. }));
+    let kill = request::Kill {
+        target: c[1].clone(),
+    };
+    game.night(&[(&wolf, kill.into())]);
+    assert!(matches!(game.latest(), State::Day { count: 3, .. }));
+
+    // 人狼が半数に達すると、狂人も人狼陣営として勝利する
+    game.vote(&c[2]);
+    let State::End { winners, .. } = game.view(&madman) else {
+        unreachable!()
+    };
+    assert_eq!(winners, vec![Winner::Team { team: Team::Wolf }]);
+    assert_eq!(game.role[&madman].team(), Team::Wolf);
+}
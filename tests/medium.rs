@@ -0,0 +1,61 @@
+mod common;
+
+use common::Game;
+use std::collections::HashMap;
+use werewolf::{
+    master::config::*,
+    request,
+    role::{Role, Team},
+    state::{Name, State},
+};
+
+#[test]
+fn medium_learns_the_team_of_each_exiled_player() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 4 },
+        wolf: WolfConfig {
+            count: 2,
+            ..Default::default()
+        },
+        medium: MediumConfig { count: 1 },
+        first_night: FirstNightConfig {
+            skip: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let medium = game.find(|role| matches!(role, Role::Medium { .. }));
+    let wolves = game.all(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+    let revelation = |game: &Game, viewer: &Name| {
+        let State::Night { role, .. } = game.view(viewer) else {
+            unreachable!()
+        };
+        match role.get(&medium) {
+            Some(Role::Medium { revelation }) => revelation.clone(),
+            _ => HashMap::new(),
+        }
+    };
+
+    game.vote(&wolves[0]);
+    assert_eq!(
+        revelation(&game, &medium),
+        [(wolves[0].clone(), Team::Wolf)].into()
+    );
+    // 霊媒の結果は霊媒師にしか見えない
+    assert!(revelation(&game, &c[0]).is_empty());
+
+    let kill = request::Kill {
+        target: c[0].clone(),
+    };
+    game.night(&[(&wolves[1], kill.into())]);
+    game.vote(&c[1]);
+    assert_eq!(
+        revelation(&game, &medium),
+        [
+            (wolves[0].clone(), Team::Wolf),
+            (c[1].clone(), Team::Citizen)
+        ]
+        .into()
+    );
+}
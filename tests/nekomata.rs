@@ -0,0 +1,75 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    request,
+    role::Role,
+    state::{Cause, State},
+};
+
+fn start(first_night: FirstNightConfig) -> Game {
+    Game::start(Config {
+        citizen: CitizenConfig { count: 4 },
+        wolf: WolfConfig {
+            count: 2,
+            ..Default::default()
+        },
+        nekomata: NekomataConfig { count: 1 },
+        first_night,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn attacked_nekomata_drags_a_wolf() {
+    let mut game = start(Default::default());
+    let nekomata = game.find(|role| *role == Role::Nekomata);
+    let wolves = game.all(Role::is_wolf);
+    let citizen = game.find(|role| *role == Role::Citizen);
+    let kill = || {
+        request::Kill {
+            target: nekomata.clone(),
+        }
+        .into()
+    };
+    game.night(&[(&wolves[0], kill()), (&wolves[1], kill())]);
+
+    // 襲撃は伏せられるが、道連れは誰にでも区別して知らされる
+    let State::Day { deaths, .. } = game.view(&citizen) else {
+        unreachable!()
+    };
+    assert_eq!(deaths.len(), 2);
+    assert_eq!(
+        (&deaths[0].name, &deaths[0].cause),
+        (&nekomata, &Cause::Night)
+    );
+    assert_eq!(deaths[1].cause, Cause::Dragged);
+    assert!(wolves.contains(&deaths[1].name));
+}
+
+#[test]
+fn exiled_nekomata_drags_a_survivor() {
+    let mut game = start(FirstNightConfig {
+        skip: true,
+        ..Default::default()
+    });
+    let nekomata = game.find(|role| *role == Role::Nekomata);
+    let citizen = game.find(|role| *role == Role::Citizen);
+    game.vote(&nekomata);
+
+    let State::Night {
+        deaths, survivors, ..
+    } = game.view(&citizen)
+    else {
+        unreachable!()
+    };
+    assert_eq!(deaths.len(), 2);
+    assert_eq!(
+        (&deaths[0].name, &deaths[0].cause),
+        (&nekomata, &Cause::Exile)
+    );
+    assert_eq!(deaths[1].cause, Cause::Dragged);
+    assert_ne!(deaths[1].name, nekomata);
+    assert_eq!(survivors.len(), 5);
+}
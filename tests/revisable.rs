@@ -0,0 +1,201 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    request,
+    role::{Role, Team},
+    state::{Cause, Death, NightStage, Phase, State},
+};
+
+fn config(first_night: FirstNightConfig) -> Config {
+    Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        action: ActionConfig { revisable: true },
+        first_night,
+        ..Default::default()
+    }
+}
+
+/// 生存者全員が行動を確定する
+fn ready_all(game: &mut Game) {
+    for name in game.survivors() {
+        game.execute(&name, request::Ready()).unwrap();
+    }
+}
+
+#[test]
+fn votes_can_be_revised_until_ready() {
+    let mut game = Game::start(config(FirstNightConfig {
+        skip: true,
+        ..Default::default()
+    }));
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+    let vote = |target: &String| request::Vote {
+        target: Some(target.clone()),
+    };
+
+    // 行動する前には確定できない
+    assert!(matches!(
+        game.validate(&c[0], request::Ready()),
+        Err(request::Error::ActionRequired)
+    ));
+
+    // 確定するまでは投票先を変更できる
+    game.execute(&c[0], vote(&c[1])).unwrap();
+    game.execute(&c[0], vote(&c[2])).unwrap();
+    game.execute(&c[0], request::Ready()).unwrap();
+    assert!(matches!(
+        game.validate(&c[0], vote(&c[1])),
+        Err(request::Error::MultipleActions)
+    ));
+    assert!(matches!(
+        game.validate(&c[0], request::Ready()),
+        Err(request::Error::MultipleActions)
+    ));
+
+    // 全員が投票しても、全員が確定するまでは場面が進まない
+    game.execute(&wolf, vote(&c[2])).unwrap();
+    game.execute(&c[1], vote(&c[2])).unwrap();
+    game.execute(&c[2], vote(&c[1])).unwrap();
+    for name in [&wolf, &c[1]] {
+        game.execute(name, request::Ready()).unwrap();
+    }
+    assert_eq!(game.view(&c[0]).phase(), Phase::Day(1));
+    game.execute(&c[2], request::Ready()).unwrap();
+
+    // 変更後の投票で集計される
+    let State::Night { deaths, tally, .. } = game.view(&c[0]) else {
+        unreachable!()
+    };
+    assert_eq!(
+        deaths,
+        vec![Death {
+            name: c[2].clone(),
+            cause: Cause::Exile
+        }]
+    );
+    assert_eq!(tally[&c[2]], 3);
+}
+
+#[test]
+fn night_actions_can_be_revised_and_divinations_wait_for_dawn() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 2 },
+        seer: SeerConfig {
+            count: 1,
+            ..Default::default()
+        },
+        ..config(Default::default())
+    });
+    let wolf = game.find(Role::is_wolf);
+    let seer = game.find(|role| matches!(role, Role::Seer { .. }));
+    let c = game.all(|role| *role == Role::Citizen);
+    let prediction = |game: &Game| {
+        let (State::Day { role, .. } | State::Night { role, .. }) = game.view(&seer) else {
+            unreachable!()
+        };
+        let Role::Seer { prediction, .. } = &role[&seer] else {
+            unreachable!()
+        };
+        prediction.clone()
+    };
+
+    // 襲撃先を変更する
+    let kill = |target: &String| request::Kill {
+        target: target.clone(),
+    };
+    game.execute(&wolf, kill(&c[0])).unwrap();
+    let divine = request::Divine {
+        target: wolf.clone(),
+    };
+    game.night(&[(&wolf, kill(&c[1]).into()), (&seer, divine.into())]);
+
+    // 占いの結果は、行動を確定して夜が明けるまで伏せられる
+    assert!(prediction(&game).is_empty());
+    ready_all(&mut game);
+    assert_eq!(prediction(&game), [(wolf.clone(), Team::Wolf)].into());
+    let State::Day { deaths, .. } = game.latest() else {
+        unreachable!()
+    };
+    assert_eq!(
+        deaths,
+        vec![Death {
+            name: c[1].clone(),
+            cause: Cause::Attack
+        }]
+    );
+}
+
+#[test]
+fn witch_revises_potions_after_everyone_else_is_ready() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 4 },
+        witch: WitchConfig { count: 1 },
+        ..config(Default::default())
+    });
+    let wolf = game.find(Role::is_wolf);
+    let witch = game.find(|role| matches!(role, Role::Witch { .. }));
+    let c = game.all(|role| *role == Role::Citizen);
+
+    let kill = request::Kill {
+        target: c[0].clone(),
+    };
+    game.night(&[(&wolf, kill.into())]);
+    ready_all(&mut game);
+
+    // 魔女だけが、行動を確定していない状態に戻る
+    let State::Night {
+        stage,
+        waiting,
+        ready,
+        ..
+    } = game.view(&witch)
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        stage,
+        NightStage::Witch {
+            attack: Some(c[0].clone())
+        }
+    );
+    assert_eq!(waiting, [witch.clone()].into());
+    assert!(!ready.contains(&witch));
+    // 他の人には、全員が確定した夜に見える
+    let State::Night { waiting, ready, .. } = game.view(&c[1]) else {
+        unreachable!()
+    };
+    assert!(waiting.is_empty());
+    assert_eq!(ready.len(), 6);
+    assert!(matches!(
+        game.validate(&witch, request::Ready()),
+        Err(request::Error::ActionRequired)
+    ));
+
+    // 薬の使い方を変更してから確定する
+    let potion = |poison: Option<&String>| request::Potion {
+        heal: true,
+        poison: poison.cloned(),
+    };
+    game.execute(&witch, potion(None)).unwrap();
+    game.execute(&witch, potion(Some(&c[1]))).unwrap();
+    assert_eq!(game.view(&witch).phase(), Phase::Night(0));
+    game.execute(&witch, request::Ready()).unwrap();
+
+    let State::Day { deaths, .. } = game.view(&witch) else {
+        unreachable!()
+    };
+    assert_eq!(
+        deaths,
+        vec![Death {
+            name: c[1].clone(),
+            cause: Cause::Poison
+        }]
+    );
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    request,
+    role::Role,
+    state::{Cause, Name, State},
+};
+
+#[test]
+fn night_causes_are_visible_only_to_those_who_know() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 2 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        seer: SeerConfig {
+            count: 1,
+            ..Default::default()
+        },
+        fox: FoxConfig { count: 1 },
+        ..Default::default()
+    });
+    let seer = game.find(|role| matches!(role, Role::Seer { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let fox = game.find(|role| *role == Role::Fox);
+    let citizen = game.find(|role| *role == Role::Citizen);
+
+    let divine = request::Divine {
+        target: fox.clone(),
+    };
+    let kill = request::Kill {
+        target: citizen.clone(),
+    };
+    game.night(&[(&seer, divine.into()), (&wolf, kill.into())]);
+    let cause = |viewer: &Name, dead: &Name| {
+        let State::Day { deaths, .. } = game.view(viewer) else {
+            unreachable!()
+        };
+        deaths
+            .into_iter()
+            .find(|death| death.name == *dead)
+            .unwrap()
+            .cause
+    };
+    assert_eq!(cause(&seer, &fox), Cause::Curse);
+    assert_eq!(cause(&seer, &citizen), Cause::Night);
+    assert_eq!(cause(&wolf, &citizen), Cause::Attack);
+    assert_eq!(cause(&wolf, &fox), Cause::Night);
+}
@@ -0,0 +1,249 @@
+mod common;
+
+use common::Game;
+use werewolf::{
+    master::config::*,
+    request,
+    role::Role,
+    state::{Name, State},
+};
+
+/// 二人の市民が同票になる投票をrounds回行い、その二人と最後の状態を返す
+fn tie(tie_break: TieBreak, rounds: usize) -> (Vec<Name>, State) {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 5 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        vote: VoteConfig {
+            tie_break,
+            ..Default::default()
+        },
+        first_night: FirstNightConfig {
+            skip: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let tied: Vec<_> = game
+        .all(|role| *role == Role::Citizen)
+        .into_iter()
+        .take(2)
+        .collect();
+    for _ in 0..rounds {
+        // 同票の二人は互いに投票し、残りの四人は二人に交互に投票する
+        let mut others = 0;
+        for name in game.survivors() {
+            let target = match tied.iter().position(|tied| *tied == name) {
+                Some(i) => tied[1 - i].clone(),
+                None => {
+                    others += 1;
+                    tied[others % 2].clone()
+                }
+            };
+            let vote = request::Vote {
+                target: Some(target),
+            };
+            game.execute(&name, vote).unwrap();
+        }
+    }
+    (tied, game.latest())
+}
+
+#[test]
+fn runoff_repeats_until_decided() {
+    let (tied, state) = tie(TieBreak::Runoff, 3);
+    let State::Day {
+        runoff, candidates, ..
+    } = state
+    else {
+        unreachable!()
+    };
+    assert_eq!(runoff, 3);
+    assert_eq!(candidates, tied.into_iter().collect());
+}
+
+#[test]
+fn runoff_once_then_random() {
+    assert!(matches!(
+        tie(TieBreak::RunoffOnceThenRandom, 1).1,
+        State::Day { runoff: 1, .. }
+    ));
+    let (tied, state) = tie(TieBreak::RunoffOnceThenRandom, 2);
+    let State::Night {
+        deaths, tie_break, ..
+    } = state
+    else {
+        unreachable!()
+    };
+    assert_eq!(deaths.len(), 1);
+    assert!(tied.contains(&deaths[0].name));
+    assert_eq!(tie_break, Some(TieBreak::RunoffOnceThenRandom));
+}
+
+#[test]
+fn runoff_once_then_nobody() {
+    assert!(matches!(
+        tie(TieBreak::RunoffOnceThenNobody, 1).1,
+        State::Day { runoff: 1, .. }
+    ));
+    let State::Night {
+        deaths, tie_break, ..
+    } = tie(TieBreak::RunoffOnceThenNobody, 2).1
+    else {
+        unreachable!()
+    };
+    assert!(deaths.is_empty());
+    assert_eq!(tie_break, Some(TieBreak::RunoffOnceThenNobody));
+}
+
+#[test]
+fn all_exiled() {
+    let (tied, state) = tie(TieBreak::AllExiled, 1);
+    let State::Night {
+        deaths, survivors, ..
+    } = state
+    else {
+        unreachable!()
+    };
+    let exiled: Vec<_> = deaths.into_iter().map(|death| death.name).collect();
+    assert_eq!(exiled, tied);
+    assert!(tied.iter().all(|name| !survivors.contains(name)));
+}
+
+#[test]
+fn runoff_limit() {
+    assert!(matches!(
+        tie(TieBreak::RunoffLimit(2), 2).1,
+        State::Day { runoff: 2, .. }
+    ));
+    let State::Night {
+        deaths, tie_break, ..
+    } = tie(TieBreak::RunoffLimit(2), 3).1
+    else {
+        unreachable!()
+    };
+    assert!(deaths.is_empty());
+    assert_eq!(tie_break, Some(TieBreak::RunoffLimit(2)));
+}
+
+#[test]
+fn no_exile_wins_ties() {
+    let mut game = Game::start(Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        vote: VoteConfig {
+            no_exile: true,
+            ..Default::default()
+        },
+        first_night: FirstNightConfig {
+            skip: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    let names = game.survivors();
+    // 一人目: 2票, 誰も追放しない: 2票
+    let targets = [None, None, Some(&names[0]), Some(&names[0])];
+    for (name, target) in names.iter().zip(targets) {
+        let vote = request::Vote {
+            target: target.cloned(),
+        };
+        game.execute(name, vote).unwrap();
+    }
+    let State::Night {
+        deaths,
+        tally,
+        no_exile,
+        ..
+    } = game.view(&names[0])
+    else {
+        unreachable!()
+    };
+    assert!(deaths.is_empty());
+    assert_eq!(tally[&names[0]], 2);
+    assert_eq!(no_exile, 2);
+}
+
+fn abstainable(abstainable: bool) -> Game {
+    Game::start(Config {
+        citizen: CitizenConfig { count: 3 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        vote: VoteConfig {
+            abstainable,
+            ..Default::default()
+        },
+        first_night: FirstNightConfig {
+            skip: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+}
+
+#[test]
+fn abstentions_are_not_counted() {
+    let game = abstainable(false);
+    let name = &game.survivors()[0];
+    assert!(matches!(
+        game.validate(name, request::Abstain()),
+        Err(request::Error::Disallowed)
+    ));
+
+    let mut game = abstainable(true);
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+    let vote = request::Vote {
+        target: Some(c[0].clone()),
+    };
+    // 棄権した人は、それ以上投票できない
+    for name in [&c[0], &c[2]] {
+        game.execute(name, request::Abstain()).unwrap();
+    }
+    assert!(matches!(
+        game.validate(&c[0], vote.clone()),
+        Err(request::Error::MultipleActions)
+    ));
+    game.execute(&wolf, vote.clone()).unwrap();
+    game.execute(&c[1], vote).unwrap();
+    let State::Night {
+        deaths,
+        tally,
+        no_exile,
+        ..
+    } = game.view(&c[1])
+    else {
+        unreachable!()
+    };
+    assert_eq!(deaths.len(), 1);
+    assert_eq!(deaths[0].name, c[0]);
+    assert_eq!(tally, [(c[0].clone(), 2)].into());
+    assert_eq!(no_exile, 0);
+}
+
+#[test]
+fn nobody_is_exiled_when_everyone_abstains() {
+    let mut game = abstainable(true);
+    for name in game.survivors() {
+        game.execute(&name, request::Abstain()).unwrap();
+    }
+    let State::Night {
+        deaths,
+        tally,
+        no_exile,
+        ..
+    } = game.latest()
+    else {
+        unreachable!()
+    };
+    assert!(deaths.is_empty());
+    assert!(tally.is_empty());
+    assert_eq!(no_exile, 0);
+}
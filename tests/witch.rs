@@ -0,0 +1,178 @@
+mod common;
+
+use common::Game;
+use std::time::Duration;
+use werewolf::{
+    master::config::*,
+    request,
+    role::Role,
+    state::{Cause, Death, NightStage, State},
+};
+
+fn config() -> Config {
+    Config {
+        citizen: CitizenConfig { count: 6 },
+        wolf: WolfConfig {
+            count: 1,
+            ..Default::default()
+        },
+        witch: WitchConfig { count: 1 },
+        ..Default::default()
+    }
+}
+
+fn start() -> Game {
+    Game::start(config())
+}
+
+#[test]
+fn witch_stage_is_hidden_from_others() {
+    let mut game = start();
+    let witch = game.find(|role| matches!(role, Role::Witch { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+
+    let kill = request::Kill {
+        target: c[0].clone(),
+    };
+    game.night(&[(&wolf, kill.into())]);
+
+    // 襲撃先は魔女にだけ知らされる
+    let State::Night { stage, waiting, .. } = game.view(&witch) else {
+        unreachable!()
+    };
+    assert_eq!(
+        stage,
+        NightStage::Witch {
+            attack: Some(c[0].clone())
+        }
+    );
+    assert_eq!(waiting, [witch.clone()].into());
+
+    // 他の人には、全員の行動が揃った夜に見える
+    for name in [&wolf, &c[1]] {
+        let State::Night { stage, waiting, .. } = game.view(name) else {
+            unreachable!()
+        };
+        assert_eq!(stage, NightStage::Main);
+        assert!(waiting.is_empty());
+    }
+
+    // 魔女以外には、魔女の行動する段階かどうかも知らせない
+    let potion = request::Potion {
+        heal: true,
+        poison: None,
+    };
+    assert!(matches!(
+        game.validate(&c[1], potion),
+        Err(request::Error::InvalidRole { .. })
+    ));
+}
+
+#[test]
+fn witch_uses_each_potion_once() {
+    let mut game = start();
+    let witch = game.find(|role| matches!(role, Role::Witch { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+    let kill = |target: &String| {
+        request::Kill {
+            target: target.clone(),
+        }
+        .into()
+    };
+    let heal = request::Potion {
+        heal: true,
+        poison: None,
+    };
+
+    // 治療の薬で襲撃された人を助ける
+    game.night(&[(&wolf, kill(&c[0]))]);
+    game.execute(&witch, heal.clone()).unwrap();
+    let State::Day { deaths, .. } = game.view(&witch) else {
+        unreachable!()
+    };
+    assert!(deaths.is_empty());
+    game.vote(&c[5]);
+
+    // 治療の薬は使い切っているので、毒の薬だけを使う
+    game.night(&[(&wolf, kill(&c[1]))]);
+    assert!(matches!(
+        game.validate(&witch, heal),
+        Err(request::Error::Disallowed)
+    ));
+    let poison = request::Potion {
+        heal: false,
+        poison: Some(c[2].clone()),
+    };
+    game.execute(&witch, poison).unwrap();
+    let State::Day { deaths, .. } = game.view(&witch) else {
+        unreachable!()
+    };
+    assert_eq!(
+        deaths,
+        vec![
+            Death {
+                name: c[1].clone(),
+                cause: Cause::Night
+            },
+            Death {
+                name: c[2].clone(),
+                cause: Cause::Poison
+            },
+        ]
+    );
+    game.vote(&c[4]);
+
+    // 薬が残っていなければ、魔女の行動する段階を飛ばして朝になる
+    game.night(&[(&wolf, kill(&c[0]))]);
+    let State::Day { count, deaths, .. } = game.view(&witch) else {
+        unreachable!()
+    };
+    assert_eq!(count, 5);
+    assert_eq!(deaths.len(), 1);
+}
+
+#[test]
+fn witch_stage_keeps_the_night_deadline() {
+    let mut game = Game::start(Config {
+        time_limit: TimeLimitConfig {
+            night: Some(Duration::from_secs(60)),
+            ..Default::default()
+        },
+        ..config()
+    });
+    let witch = game.find(|role| matches!(role, Role::Witch { .. }));
+    let wolf = game.find(Role::is_wolf);
+    let c = game.all(|role| *role == Role::Citizen);
+    let deadline = game.master.deadline();
+    assert!(deadline.is_some());
+
+    // 魔女の行動する段階に入っても、締め切りは変わらない
+    let kill = request::Kill {
+        target: c[0].clone(),
+    };
+    game.night(&[(&wolf, kill.into())]);
+    assert!(matches!(
+        game.view(&witch),
+        State::Night {
+            stage: NightStage::Witch { .. },
+            ..
+        }
+    ));
+    assert_eq!(game.master.deadline(), deadline);
+
+    // 夜の締め切りを過ぎると、魔女は薬を使わずに朝になる
+    assert!(game.expire());
+    let State::Day { deaths, .. } = game.latest() else {
+        unreachable!()
+    };
+    assert_eq!(
+        deaths,
+        vec![Death {
+            name: c[0].clone(),
+            cause: Cause::Attack
+        }]
+    );
+    assert_ne!(game.master.deadline(), deadline);
+}